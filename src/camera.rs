@@ -9,7 +9,7 @@ pub struct CameraPlugin;
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(setup)
-            .add_system(zooming)
+            .add_system(zooming.run_not_in_state(TurnState::Loading))
             .add_system_to_stage(CoreStage::PostUpdate, track_player_avatar);
     }
 }
@@ -22,14 +22,17 @@ fn setup(mut commands: Commands) {
 
 fn zooming(
     keyboard_input: Res<Input<KeyCode>>,
+    input_bindings: Res<InputBindings>,
     mut query: Query<&mut OrthographicProjection, (With<Camera>, With<TracksPlayer>)>,
 ) {
+    let is_pressed =
+        |action| keyboard_input.any_pressed(input_bindings.keys(action).iter().cloned());
     for mut ortho in query.iter_mut() {
-        if keyboard_input.pressed(KeyCode::Z) {
+        if is_pressed(InputAction::ZoomOut) {
             ortho.scale += 0.1;
         }
 
-        if keyboard_input.pressed(KeyCode::X) {
+        if is_pressed(InputAction::ZoomIn) {
             ortho.scale -= 0.1;
         }
 
@@ -46,7 +49,7 @@ fn track_player_avatar(
     if let Some(new_player_pos) = player_query
         .iter()
         .next()
-        .map(|transform| transform.translation.clone())
+        .map(|transform| transform.translation)
     {
         for mut camera_transform in camera_query.iter_mut() {
             let z = camera_transform.translation.z;
@@ -5,12 +5,17 @@ use bevy::app::PluginGroupBuilder;
 use bevy::reflect::TypeUuid;
 use bevy_asset_loader::{AssetCollection, AssetLoader};
 use bevy_asset_ron::RonAssetPlugin;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum EffectData {
     Healing(u32),
     RevealMap,
+    Unlock(u32),
+    Poison { damage: u32, turns: u32 },
+    Regeneration { amount: u32, turns: u32 },
+    Haste { turns: u32 },
+    Confusion { turns: u32 },
 }
 
 #[derive(Clone, Debug, Deserialize, TypeUuid)]
@@ -20,14 +25,370 @@ pub struct ItemData {
     pub icon: String,
     pub frequency: f32,
     pub effects: Vec<EffectData>,
+    #[serde(default)]
+    pub equipment: Option<EquipmentData>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum EquipmentSlot {
+    Weapon,
+    Armour,
+    Ring,
+}
+
+// Bonuses to the wearer's combat stats while the item is equipped
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EquipmentData {
+    pub slot: EquipmentSlot,
+    #[serde(default)]
+    pub attack: i32,
+    #[serde(default)]
+    pub defense: i32,
+    #[serde(default)]
+    pub damage: i32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum BehaviorData {
+    ChasingPlayer,
+    Swimming,
+}
+
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "6c1b5c3e-2a0d-4f4e-9d37-81b0f2a4c5d9"]
+pub struct MobData {
+    pub name: String,
+    pub glyph: usize,
+    pub health: u32,
+    pub combat: CombatStats,
+    pub xp: u32,
+    #[serde(default)]
+    pub loot: Option<String>,
+    pub fov_radius: i32,
+    pub speed: i32,
+    pub frequency: f32,
+    pub behaviors: Vec<BehaviorData>,
 }
 
+// Starting stats of the player, which is loaded on its own so it never turns up among the mobs
 #[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "c4e1f7a2-8d3b-4b6e-9a05-2f7d1c8e6b39"]
+pub struct PlayerData {
+    pub glyph: usize,
+    pub health: u32,
+    pub combat: CombatStats,
+    pub fov_radius: i32,
+    pub speed: i32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, TypeUuid)]
 #[uuid = "0f8f2dfb-6fba-4f34-b258-358b9438e3f9"]
 pub struct BiomeData {
     pub name: String,
     pub levels: Vec<usize>,
     pub tile_textures: HashMap<TileType, u16>,
+    pub architect: ArchitectData,
+    #[serde(default)]
+    pub connectivity: ConnectivityStrategy,
+    #[serde(default)]
+    pub locks: Vec<u32>,
+    #[serde(default)]
+    pub vaults: Vec<String>,
+    #[serde(default)]
+    pub terrain: Vec<TerrainData>,
+    #[serde(default)]
+    pub loot: Option<BiomeLootData>,
+}
+
+impl BiomeData {
+    pub fn tile_texture(&self, tile_type: TileType) -> Option<u16> {
+        // Locked doors look like any other closed door unless given their own texture
+        self.tile_textures
+            .get(&tile_type)
+            .cloned()
+            .or_else(|| match tile_type {
+                TileType::DoorLocked(_) => self.tile_textures.get(&TileType::DoorClosed).cloned(),
+                _ => None,
+            })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub enum ConnectivityStrategy {
+    #[default]
+    FillPockets,
+    TunnelPockets,
+}
+
+// Items placed on each level are drawn from the named loot table rather than by item frequency
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BiomeLootData {
+    pub table: String,
+    pub rolls: usize,
+}
+
+// Pools of the given tile type grown over open floor
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TerrainData {
+    pub tile: TileType,
+    pub pools: usize,
+    pub pool_size: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ArchitectData {
+    Rooms {
+        num_rooms: usize,
+        min_room_size: i32,
+        max_room_size: i32,
+    },
+    Bsp {
+        min_leaf_size: i32,
+        min_room_size: i32,
+    },
+    CellularAutomata {
+        fill_percentage: u32,
+        iterations: usize,
+    },
+    DrunkardsWalk {
+        floor_percentage: usize,
+        lifetime: usize,
+        spawn: DrunkardSpawn,
+    },
+    WaveFunctionCollapse {
+        sample: String,
+        pattern_size: usize,
+        fallback: Box<ArchitectData>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum DrunkardSpawn {
+    Center,
+    RandomFloor,
+}
+
+impl ArchitectData {
+    // Clamps parameters which would make map generation panic or never finish
+    fn validate(&mut self, biome: &str) {
+        match self {
+            ArchitectData::Rooms {
+                num_rooms,
+                min_room_size,
+                max_room_size,
+            } => {
+                // Rooms have to fit inside the wall around the edge of the map
+                let largest_room = MAP_WIDTH.min(MAP_HEIGHT) as i32 - 3;
+                let valid_max = (*max_room_size).clamp(1, largest_room);
+                let valid_min = (*min_room_size).clamp(1, valid_max);
+                let valid_num = (*num_rooms).max(1);
+                if (valid_num, valid_min, valid_max) != (*num_rooms, *min_room_size, *max_room_size)
+                {
+                    warn!(
+                        "Invalid rooms in biome {}, using {} rooms of size {} to {}",
+                        biome, valid_num, valid_min, valid_max
+                    );
+                    *num_rooms = valid_num;
+                    *min_room_size = valid_min;
+                    *max_room_size = valid_max;
+                }
+            }
+            ArchitectData::Bsp {
+                min_leaf_size,
+                min_room_size,
+            } => {
+                // The whole map is the first leaf, and every leaf needs room for a walled room
+                let smallest_side = MAP_WIDTH.min(MAP_HEIGHT) as i32;
+                let valid_room = (*min_room_size).clamp(1, smallest_side - 3);
+                let valid_leaf = (*min_leaf_size).clamp(valid_room + 2, smallest_side);
+                if (valid_leaf, valid_room) != (*min_leaf_size, *min_room_size) {
+                    warn!(
+                        "Invalid partitioning in biome {}, using leaves of size {} and rooms of size {}",
+                        biome, valid_leaf, valid_room
+                    );
+                    *min_leaf_size = valid_leaf;
+                    *min_room_size = valid_room;
+                }
+            }
+            ArchitectData::DrunkardsWalk {
+                floor_percentage,
+                lifetime,
+                ..
+            } => {
+                // Without any floor there is nowhere to place the player
+                let valid_percentage = (*floor_percentage).clamp(1, 100);
+                let valid_lifetime = (*lifetime).max(1);
+                if (valid_percentage, valid_lifetime) != (*floor_percentage, *lifetime) {
+                    warn!(
+                        "Invalid drunkard's walk in biome {}, using {}% floor and a lifetime of {}",
+                        biome, valid_percentage, valid_lifetime
+                    );
+                    *floor_percentage = valid_percentage;
+                    *lifetime = valid_lifetime;
+                }
+            }
+            ArchitectData::CellularAutomata {
+                fill_percentage,
+                iterations,
+            } => {
+                // Outside of this range the automaton tends to turn the whole map into wall
+                let valid_percentage = (*fill_percentage).clamp(30, 70);
+                let valid_iterations = (*iterations).min(20);
+                if (valid_percentage, valid_iterations) != (*fill_percentage, *iterations) {
+                    warn!(
+                        "Invalid cellular automata in biome {}, using {}% fill and {} iterations",
+                        biome, valid_percentage, valid_iterations
+                    );
+                    *fill_percentage = valid_percentage;
+                    *iterations = valid_iterations;
+                }
+            }
+            ArchitectData::WaveFunctionCollapse { fallback, .. } => fallback.validate(biome),
+        }
+    }
+}
+
+impl Default for ArchitectData {
+    fn default() -> Self {
+        ArchitectData::CellularAutomata {
+            fill_percentage: 55,
+            iterations: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "b3f7d2a9-6c4e-4f1b-9a8d-2e5c7f0b1d64"]
+pub struct LootTableData {
+    pub name: String,
+    pub entries: Vec<LootEntryData>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LootEntryData {
+    pub weight: u32,
+    pub drop: LootDrop,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum LootDrop {
+    Nothing,
+    Gold(Dice),
+    Item(String),
+}
+
+// Rows of the grid run from north to south; characters missing from the legend leave
+// the underlying map untouched
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "9d2e4b7a-1f3c-4a8e-b6d5-7c0e2f9a4b18"]
+pub struct VaultData {
+    pub name: String,
+    pub grid: Vec<String>,
+    pub legend: HashMap<char, VaultCell>,
+}
+
+// Sample maps are treated as wrapping around at their edges; characters missing from the
+// legend are walls
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "5e8a1c3f-9b2d-4e7a-8f16-0d4c7b9e2a53"]
+pub struct SampleMapData {
+    pub name: String,
+    pub grid: Vec<String>,
+    pub legend: HashMap<char, TileType>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum VaultCell {
+    Tile(TileType),
+    Mob(String),
+    Item(String),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+pub enum Direction {
+    North,
+    South,
+    West,
+    East,
+    NorthWest,
+    NorthEast,
+    SouthWest,
+    SouthEast,
+}
+
+impl Direction {
+    pub fn delta(&self) -> IVec2 {
+        match self {
+            Direction::North => ivec2(0, 1),
+            Direction::South => ivec2(0, -1),
+            Direction::West => ivec2(-1, 0),
+            Direction::East => ivec2(1, 0),
+            Direction::NorthWest => ivec2(-1, 1),
+            Direction::NorthEast => ivec2(1, 1),
+            Direction::SouthWest => ivec2(-1, -1),
+            Direction::SouthEast => ivec2(1, -1),
+        }
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        let delta = self.delta();
+        delta.x != 0 && delta.y != 0
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+pub enum InputAction {
+    Move(Direction),
+    Wait,
+    Pause,
+    Explore,
+    CloseDoors,
+    ZoomIn,
+    ZoomOut,
+    UseInventorySlot(usize),
+}
+
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "3a5d9e61-7c2b-4f0e-a8d4-5b9c1e2f7a30"]
+pub struct InputBindings {
+    pub bindings: HashMap<InputAction, Vec<KeyCode>>,
+}
+
+impl InputBindings {
+    pub fn action(&self, key: KeyCode) -> Option<InputAction> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(&action, _)| action)
+    }
+
+    pub fn keys(&self, action: InputAction) -> &[KeyCode] {
+        self.bindings
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    // Keys bound to several actions trigger whichever of them happens to be found first
+    fn duplicate_keys(&self) -> Vec<(KeyCode, Vec<InputAction>)> {
+        let mut actions_by_key: HashMap<KeyCode, Vec<InputAction>> = HashMap::new();
+        for (&action, keys) in &self.bindings {
+            for &key in keys {
+                actions_by_key.entry(key).or_default().push(action);
+            }
+        }
+        actions_by_key
+            .into_iter()
+            .filter(|(_, actions)| actions.len() > 1)
+            .collect()
+    }
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        ron::from_str(include_str!("../assets/config/default.bindings"))
+            .expect("The default input bindings should be valid")
+    }
 }
 
 #[derive(AssetCollection)]
@@ -41,11 +402,32 @@ struct DataAssets {
     #[asset(key = "data.items", collection(typed))]
     _item_data: Vec<Handle<ItemData>>,
 
+    #[asset(key = "data.mobs", collection(typed))]
+    _mob_data: Vec<Handle<MobData>>,
+
+    #[asset(key = "data.player")]
+    player_data: Handle<PlayerData>,
+
     #[asset(key = "data.biomes", collection(typed))]
     _biome_data: Vec<Handle<BiomeData>>,
 
+    #[asset(key = "data.vaults", collection(typed))]
+    _vault_data: Vec<Handle<VaultData>>,
+
+    #[asset(key = "data.samples", collection(typed))]
+    _sample_map_data: Vec<Handle<SampleMapData>>,
+
+    #[asset(key = "data.loot_tables", collection(typed))]
+    _loot_table_data: Vec<Handle<LootTableData>>,
+
     #[asset(key = "images.items", collection(typed))]
     _item_images: Vec<Handle<Image>>,
+
+    #[asset(key = "images.status", collection(typed))]
+    _status_images: Vec<Handle<Image>>,
+
+    #[asset(key = "config.input_bindings")]
+    input_bindings: Handle<InputBindings>,
 }
 
 pub struct DataPlugins;
@@ -54,7 +436,13 @@ impl PluginGroup for DataPlugins {
     fn build(&mut self, group: &mut PluginGroupBuilder) {
         group
             .add(RonAssetPlugin::<ItemData>::new(&["item"]))
+            .add(RonAssetPlugin::<MobData>::new(&["mob"]))
+            .add(RonAssetPlugin::<PlayerData>::new(&["player"]))
             .add(RonAssetPlugin::<BiomeData>::new(&["biome"]))
+            .add(RonAssetPlugin::<VaultData>::new(&["vault"]))
+            .add(RonAssetPlugin::<SampleMapData>::new(&["sample"]))
+            .add(RonAssetPlugin::<LootTableData>::new(&["loot"]))
+            .add(RonAssetPlugin::<InputBindings>::new(&["bindings"]))
             .add(DataLoaderPlugin);
     }
 }
@@ -68,5 +456,42 @@ impl Plugin for DataLoaderPlugin {
             .with_dynamic_asset_collection_file("manifest.assets")
             .with_collection::<DataAssets>()
             .build(app);
+        app.add_exit_system(TurnState::Loading, insert_input_bindings)
+            .add_exit_system(TurnState::Loading, insert_player_data)
+            .add_exit_system(TurnState::Loading, validate_biomes);
+    }
+}
+
+fn insert_player_data(
+    mut commands: Commands,
+    data_assets: Res<DataAssets>,
+    player_data: Res<Assets<PlayerData>>,
+) {
+    if let Some(player_data) = player_data.get(&data_assets.player_data) {
+        commands.insert_resource(player_data.clone());
+    }
+}
+
+fn validate_biomes(mut biomes: ResMut<Assets<BiomeData>>) {
+    for (_, biome) in biomes.iter_mut() {
+        biome.architect.validate(&biome.name);
+    }
+}
+
+fn insert_input_bindings(
+    mut commands: Commands,
+    data_assets: Res<DataAssets>,
+    input_bindings: Res<Assets<InputBindings>>,
+) {
+    let bindings = input_bindings
+        .get(&data_assets.input_bindings)
+        .cloned()
+        .unwrap_or_else(|| {
+            warn!("Failed to load the input bindings, using the defaults");
+            InputBindings::default()
+        });
+    for (key, actions) in bindings.duplicate_keys() {
+        warn!("Key {:?} is bound to several actions: {:?}", key, actions);
     }
+    commands.insert_resource(bindings);
 }
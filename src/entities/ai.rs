@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use bevy::ecs::system::EntityCommands;
 
 pub struct AiPlugin;
 
@@ -11,8 +12,18 @@ impl Plugin for AiPlugin {
     }
 }
 
+pub fn apply_behavior_to_mob(entity_commands: &mut EntityCommands, behavior: &BehaviorData) {
+    match behavior {
+        BehaviorData::ChasingPlayer => entity_commands.insert(ChasingPlayer),
+        BehaviorData::Swimming => entity_commands.insert(Swimmer),
+    };
+}
+
 pub fn chasing(
-    movers: Query<(Entity, &Position, &FieldOfView), With<ChasingPlayer>>,
+    movers: Query<
+        (Entity, &Position, &FieldOfView, &Energy, Option<&Swimmer>),
+        With<ChasingPlayer>,
+    >,
     player: Query<&Position, With<Player>>,
     map_query: TileMapQuery,
     mut msgs: EventWriter<WantsToMove>,
@@ -21,15 +32,37 @@ pub fn chasing(
     let &player_pos = player.single();
     let player_idx = map.point2d_to_index(player_pos.into());
 
+    let ready_movers: Vec<_> = movers
+        .iter()
+        .filter(|&(_, _, fov, energy, _)| energy.is_ready() && fov.can_see(player_pos))
+        .collect();
+
+    // Swimmers take routes through deep water which others can't follow
     let search_targets = vec![player_idx];
-    let dijkstra_map = DijkstraMap::new(MAP_WIDTH, MAP_HEIGHT, &search_targets, &map, 1024.0);
+    let walking_map = DijkstraMap::new(MAP_WIDTH, MAP_HEIGHT, &search_targets, &map, 1024.0);
+    let swimming_map = if ready_movers.iter().any(|(.., swimmer)| swimmer.is_some()) {
+        map.set_can_swim(true);
+        Some(DijkstraMap::new(
+            MAP_WIDTH,
+            MAP_HEIGHT,
+            &search_targets,
+            &map,
+            1024.0,
+        ))
+    } else {
+        None
+    };
 
-    movers
-        .iter()
-        .filter(|&(_, _, fov)| fov.can_see(player_pos))
-        .for_each(|(entity, &pos, _)| {
+    ready_movers
+        .into_iter()
+        .for_each(|(entity, &pos, _, _, swimmer)| {
             let idx = map.point2d_to_index(pos.into());
-            if let Some(destination) = DijkstraMap::find_lowest_exit(&dijkstra_map, idx, &map) {
+            map.set_can_swim(swimmer.is_some());
+            let dijkstra_map = match (&swimming_map, swimmer) {
+                (Some(swimming_map), Some(_)) => swimming_map,
+                _ => &walking_map,
+            };
+            if let Some(destination) = DijkstraMap::find_lowest_exit(dijkstra_map, idx, &map) {
                 let distance = DistanceAlg::Pythagoras.distance2d(pos.into(), player_pos.into());
                 let destination = if distance > 1.2 {
                     map.index_to_point2d(destination).into()
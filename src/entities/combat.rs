@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use rand::Rng;
 
 pub struct CombatPlugin;
 
@@ -9,29 +10,108 @@ impl Plugin for CombatPlugin {
     }
 }
 
+// Attacks hit when d20 + attack reaches 10 + the victim's defense
+const HIT_DIFFICULTY: i32 = 10;
+
 fn combat_damage(
+    mut commands: Commands,
     mut attack_msgs: EventReader<WantsToAttack>,
     mut health_query: Query<&mut Health>,
+    stats_query: Query<&CombatStats>,
+    equipment_query: Query<(&Equipment, &CarriedBy), With<Equipped>>,
+    name_query: Query<&Name>,
+    player_query: Query<(), With<Player>>,
+    mut rng: ResMut<GameRng>,
+    mut log_msgs: EventWriter<LogMessage>,
 ) {
-    for &WantsToAttack { victim, .. } in attack_msgs.iter() {
-        if let Ok(mut victim_health) = health_query.get_mut(victim) {
-            victim_health.take_damage(1);
+    for &WantsToAttack { attacker, victim } in attack_msgs.iter() {
+        let (attacker_stats, victim_stats) =
+            match (stats_query.get(attacker), stats_query.get(victim)) {
+                (Ok(attacker_stats), Ok(victim_stats)) => (attacker_stats, victim_stats),
+                _ => continue,
+            };
+        let mut victim_health = match health_query.get_mut(victim) {
+            Ok(victim_health) => victim_health,
+            Err(_) => continue,
+        };
+
+        // Equipped items add their bonuses on top of the wearer's own stats
+        let equipment_of = |entity| {
+            equipment_query
+                .iter()
+                .filter(move |(_, &CarriedBy(carrier))| carrier == entity)
+                .map(|(Equipment(equipment), _)| equipment)
+        };
+        let attack = attacker_stats.attack
+            + equipment_of(attacker)
+                .map(|equipment| equipment.attack)
+                .sum::<i32>();
+        let defense = victim_stats.defense
+            + equipment_of(victim)
+                .map(|equipment| equipment.defense)
+                .sum::<i32>();
+        let damage_bonus: i32 = equipment_of(attacker)
+            .map(|equipment| equipment.damage)
+            .sum();
+
+        let name_of = |entity| name_query.get(entity).map_or("monster", Name::as_str);
+        let describe = |player_verb: &str, verb: &str| {
+            if player_query.get(attacker).is_ok() {
+                format!("You {} the {}", player_verb, name_of(victim))
+            } else if player_query.get(victim).is_ok() {
+                format!("The {} {} you", name_of(attacker), verb)
+            } else {
+                format!("The {} {} the {}", name_of(attacker), verb, name_of(victim))
+            }
+        };
+
+        let roll = rng.gen_range(1..=20);
+        if roll + attack < HIT_DIFFICULTY + defense {
+            log_msgs.send(LogMessage::combat(describe("miss", "misses")));
+            continue;
         }
+
+        let mut damage = (attacker_stats.damage.roll(rng.as_mut()) + damage_bonus).max(1);
+        let text = if rng.gen::<f32>() < attacker_stats.critical_chance {
+            damage *= 2;
+            describe("critically hit", "critically hits")
+        } else {
+            describe("hit", "hits")
+        };
+        let text = format!("{} for {} damage", text, damage);
+        victim_health.take_damage(damage as u32);
+        commands.entity(victim).insert(LastAttacker(attacker));
+        log_msgs.send(LogMessage::combat(text));
     }
 }
 
 fn kill_mobs(
     mut cmd: Commands,
-    health_query: Query<(Entity, &Health, Option<&Player>), Changed<Health>>,
+    health_query: Query<(Entity, &Health, Option<&Name>, Option<&Player>), Changed<Health>>,
+    reward_query: Query<(&ExperienceReward, &LastAttacker)>,
+    mut experience_query: Query<&mut Experience>,
+    mut log_msgs: EventWriter<LogMessage>,
 ) {
-    for (killed_entity, _, player_opt) in health_query
+    for (killed_entity, _, name, player_opt) in health_query
         .iter()
-        .filter(|(_, health, _)| health.is_dead())
+        .filter(|(_, health, _, _)| health.is_dead())
     {
         if player_opt.is_some() {
+            log_msgs.send(LogMessage::combat("You die"));
             cmd.insert_resource(NextState(TurnState::Defeat));
         } else {
+            let name = name.map_or("monster", Name::as_str);
+            log_msgs.send(LogMessage::combat(format!("The {} dies", name)));
             cmd.entity(killed_entity).despawn();
+
+            // Whoever landed the last blow gets the experience
+            if let Ok((&ExperienceReward(xp), &LastAttacker(attacker))) =
+                reward_query.get(killed_entity)
+            {
+                if let Ok(mut experience) = experience_query.get_mut(attacker) {
+                    experience.xp += xp;
+                }
+            }
         }
     }
 }
@@ -1,11 +1,14 @@
 use crate::prelude::*;
-use bevy::ecs::system::EntityCommands;
+use bevy::ecs::system::{Command, EntityCommands};
+use serde::{Deserialize, Serialize};
 
 pub struct EffectsPlugin;
 
 impl Plugin for EffectsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(apply_healing).add_system(reveal_map);
+        app.add_system(apply_healing)
+            .add_system(reveal_map)
+            .add_system(tick_status_effects.run_in_state(TurnState::PlayerTurn));
     }
 }
 
@@ -15,11 +18,113 @@ struct ApplyHealing(pub u32);
 #[derive(Component, Debug)]
 struct RevealMap;
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum StatusEffectKind {
+    Poison(u32),
+    Regeneration(u32),
+    Haste,
+    Confusion,
+}
+
+impl StatusEffectKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusEffectKind::Poison(_) => "poisoned",
+            StatusEffectKind::Regeneration(_) => "regenerating",
+            StatusEffectKind::Haste => "hasted",
+            StatusEffectKind::Confusion => "confused",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            StatusEffectKind::Poison(_) => "images/status/poison.png",
+            StatusEffectKind::Regeneration(_) => "images/status/regeneration.png",
+            StatusEffectKind::Haste => "images/status/haste.png",
+            StatusEffectKind::Confusion => "images/status/confusion.png",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub turns_left: u32,
+}
+
+#[derive(Clone, Component, Debug, Default, Deserialize, Serialize)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn has(&self, kind: StatusEffectKind) -> bool {
+        self.0.iter().any(|effect| effect.kind == kind)
+    }
+
+    // Applying an effect again refreshes its duration rather than stacking it
+    fn add(&mut self, effect: StatusEffect) {
+        match self.0.iter_mut().find(|other| other.kind == effect.kind) {
+            Some(other) => other.turns_left = other.turns_left.max(effect.turns_left),
+            None => self.0.push(effect),
+        }
+    }
+}
+
+struct AddStatusEffect {
+    entity: Entity,
+    effect: StatusEffect,
+}
+
+impl Command for AddStatusEffect {
+    fn write(self, world: &mut World) {
+        if let Some(mut entity) = world.get_entity_mut(self.entity) {
+            match entity.get_mut::<StatusEffects>() {
+                Some(mut status_effects) => status_effects.add(self.effect),
+                None => {
+                    entity.insert(StatusEffects(vec![self.effect]));
+                }
+            }
+        }
+    }
+}
+
 pub fn apply_effect_to_mob(entity_commands: &mut EntityCommands, effect: &EffectData) {
     match effect {
-        EffectData::Healing(amount) => entity_commands.insert(ApplyHealing(*amount)),
-        EffectData::RevealMap => entity_commands.insert(RevealMap),
-    };
+        EffectData::Healing(amount) => {
+            entity_commands.insert(ApplyHealing(*amount));
+        }
+        EffectData::RevealMap => {
+            entity_commands.insert(RevealMap);
+        }
+        // Keys take effect when walking into a locked door
+        EffectData::Unlock(_) => {}
+        &EffectData::Poison { damage, turns } => {
+            add_status_effect(entity_commands, StatusEffectKind::Poison(damage), turns);
+        }
+        &EffectData::Regeneration { amount, turns } => {
+            add_status_effect(
+                entity_commands,
+                StatusEffectKind::Regeneration(amount),
+                turns,
+            );
+        }
+        &EffectData::Haste { turns } => {
+            add_status_effect(entity_commands, StatusEffectKind::Haste, turns);
+        }
+        &EffectData::Confusion { turns } => {
+            add_status_effect(entity_commands, StatusEffectKind::Confusion, turns);
+        }
+    }
+}
+
+fn add_status_effect(entity_commands: &mut EntityCommands, kind: StatusEffectKind, turns: u32) {
+    let entity = entity_commands.id();
+    entity_commands.commands().add(AddStatusEffect {
+        entity,
+        effect: StatusEffect {
+            kind,
+            turns_left: turns,
+        },
+    });
 }
 
 fn apply_healing(
@@ -44,3 +149,56 @@ fn reveal_map(
         commands.entity(entity).remove::<RevealMap>();
     }
 }
+
+fn tick_status_effects(
+    mut mob_query: Query<(
+        &mut StatusEffects,
+        &mut Health,
+        Option<&Name>,
+        Option<&Player>,
+    )>,
+    mut log_msgs: EventWriter<LogMessage>,
+) {
+    for (mut status_effects, mut health, name, player) in mob_query.iter_mut() {
+        if status_effects.0.is_empty() {
+            continue;
+        }
+
+        for effect in status_effects.0.iter_mut() {
+            match effect.kind {
+                StatusEffectKind::Poison(damage) => {
+                    health.take_damage(damage);
+                    let text = if player.is_some() {
+                        "You suffer from the poison".to_string()
+                    } else {
+                        format!(
+                            "The {} suffers from the poison",
+                            name.map_or("monster", Name::as_str)
+                        )
+                    };
+                    log_msgs.send(LogMessage::combat(text));
+                }
+                StatusEffectKind::Regeneration(amount) => {
+                    health.heal(amount);
+                }
+                // Haste and confusion act on the mob's speed and movement while they last
+                StatusEffectKind::Haste | StatusEffectKind::Confusion => {}
+            }
+            effect.turns_left = effect.turns_left.saturating_sub(1);
+        }
+
+        for effect in status_effects
+            .0
+            .iter()
+            .filter(|effect| effect.turns_left == 0)
+        {
+            if player.is_some() {
+                log_msgs.send(LogMessage::info(format!(
+                    "You are no longer {}",
+                    effect.kind.name()
+                )));
+            }
+        }
+        status_effects.0.retain(|effect| effect.turns_left > 0);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::prelude::*;
+
+pub const ACTION_COST: i32 = 100;
+
+pub struct EnergyPlugin;
+
+impl Plugin for EnergyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spend_player_energy.run_in_state(TurnState::PlayerTurn))
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                spend_monster_energy.run_in_state(TurnState::MonsterTurn),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                gain_energy.run_in_state(TurnState::MonsterTurn),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Component, Debug, PartialEq)]
+pub struct Speed(pub i32);
+
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq)]
+pub struct Energy(pub i32);
+
+impl Energy {
+    pub fn is_ready(&self) -> bool {
+        self.0 >= ACTION_COST
+    }
+
+    pub fn spend(&mut self) {
+        self.0 -= ACTION_COST;
+    }
+}
+
+fn spend_player_energy(mut player_query: Query<&mut Energy, With<Player>>) {
+    for mut energy in player_query.iter_mut() {
+        energy.spend();
+    }
+}
+
+fn spend_monster_energy(mut mob_query: Query<&mut Energy, (With<Mob>, Without<Player>)>) {
+    for mut energy in mob_query.iter_mut().filter(|energy| energy.is_ready()) {
+        energy.spend();
+    }
+}
+
+// Hasted mobs gain energy twice as fast
+fn effective_speed(&Speed(speed): &Speed, status_effects: Option<&StatusEffects>) -> i32 {
+    match status_effects {
+        Some(status_effects) if status_effects.has(StatusEffectKind::Haste) => speed * 2,
+        _ => speed,
+    }
+}
+
+fn gain_energy(mut mob_query: Query<(&Speed, &mut Energy, Option<&StatusEffects>), With<Mob>>) {
+    // Somebody still has to act before time may advance
+    if mob_query.iter().any(|(_, energy, _)| energy.is_ready()) {
+        return;
+    }
+
+    // Skip ahead to the tick at which the next mob is ready to act
+    let ticks = mob_query
+        .iter()
+        .map(|(speed, energy, status_effects)| (effective_speed(speed, status_effects), energy))
+        .filter(|&(speed, _)| speed > 0)
+        .map(|(speed, &Energy(energy))| (ACTION_COST - energy + speed - 1) / speed)
+        .min();
+
+    if let Some(ticks) = ticks {
+        for (speed, mut energy, status_effects) in mob_query.iter_mut() {
+            energy.0 += effective_speed(speed, status_effects).max(0) * ticks;
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const XP_PER_LEVEL: u32 = 10;
+const HEALTH_PER_LEVEL: u32 = 2;
+
+pub struct ExperiencePlugin;
+
+impl Plugin for ExperiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(level_up);
+    }
+}
+
+#[derive(Clone, Component, Debug, Deserialize, Serialize)]
+pub struct Experience {
+    pub level: u32,
+    pub xp: u32,
+}
+
+impl Experience {
+    // Each level takes a little longer to reach than the one before
+    pub fn xp_to_next_level(&self) -> u32 {
+        self.level * XP_PER_LEVEL
+    }
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Experience { level: 1, xp: 0 }
+    }
+}
+
+// Experience awarded to whoever kills the mob
+#[derive(Clone, Copy, Component, Debug)]
+pub struct ExperienceReward(pub u32);
+
+#[derive(Clone, Copy, Component, Debug)]
+pub struct LastAttacker(pub Entity);
+
+fn level_up(
+    mut mob_query: Query<
+        (
+            &mut Experience,
+            &mut Health,
+            &mut CombatStats,
+            Option<&Player>,
+        ),
+        Changed<Experience>,
+    >,
+    mut log_msgs: EventWriter<LogMessage>,
+) {
+    for (mut experience, mut health, mut stats, player) in mob_query.iter_mut() {
+        while experience.xp >= experience.xp_to_next_level() {
+            experience.xp -= experience.xp_to_next_level();
+            experience.level += 1;
+
+            // Attack improves every level and defense every other level
+            health.raise_max(HEALTH_PER_LEVEL);
+            stats.attack += 1;
+            if experience.level % 2 == 0 {
+                stats.defense += 1;
+            }
+
+            if player.is_some() {
+                log_msgs.send(LogMessage::info(format!(
+                    "You reach level {}",
+                    experience.level
+                )));
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::prelude::*;
+
+const LAVA_DAMAGE: u32 = 2;
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(burn_player_in_lava.run_in_state(TurnState::PlayerTurn))
+            .add_system_to_stage(
+                CoreStage::First,
+                burn_monsters_in_lava.run_in_state(TurnState::MonsterTurn),
+            );
+    }
+}
+
+// The player spends their energy once per PlayerTurn, so they burn once per action
+fn burn_player_in_lava(
+    mut player_query: Query<(&Position, &mut Health), With<Player>>,
+    mut tile_map_query: TileMapQuery,
+    mut log_msgs: EventWriter<LogMessage>,
+) {
+    for (position, mut health) in player_query.iter_mut() {
+        if tile_map_query.tile_type(position) == Some(TileType::Lava) {
+            health.take_damage(LAVA_DAMAGE);
+            log_msgs.send(LogMessage::combat("You are burned by the lava".to_string()));
+        }
+    }
+}
+
+// Monsters burn whenever they are ready to act, which is when spend_monster_energy charges them
+fn burn_monsters_in_lava(
+    mut mob_query: Query<
+        (&Position, &Energy, &mut Health, Option<&Name>),
+        (With<Mob>, Without<Player>),
+    >,
+    mut tile_map_query: TileMapQuery,
+    mut log_msgs: EventWriter<LogMessage>,
+) {
+    for (position, _, mut health, name) in mob_query
+        .iter_mut()
+        .filter(|(_, energy, _, _)| energy.is_ready())
+    {
+        if tile_map_query.tile_type(position) != Some(TileType::Lava) {
+            continue;
+        }
+
+        health.take_damage(LAVA_DAMAGE);
+        log_msgs.send(LogMessage::combat(format!(
+            "The {} is burned by the lava",
+            name.map_or("monster", Name::as_str)
+        )));
+    }
+}
@@ -4,7 +4,9 @@ pub struct ItemPlugin;
 
 impl Plugin for ItemPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(player_picks_up_items)
+        app.add_event::<PickedUpItem>()
+            .add_system(player_picks_up_items)
+            .add_system(equip_used_items)
             .add_system(consume_used_items);
     }
 }
@@ -15,12 +17,23 @@ pub struct Item;
 #[derive(Component, Debug, PartialEq)]
 pub struct CarriedBy(pub Entity);
 
+#[derive(Debug)]
+pub struct PickedUpItem {
+    pub entity: Entity,
+}
+
 #[derive(Component, Debug)]
 pub struct Effects(pub Vec<EffectData>);
 
 #[derive(Component, Debug)]
 pub struct Used;
 
+#[derive(Component, Debug)]
+pub struct Equipment(pub EquipmentData);
+
+#[derive(Component, Debug)]
+pub struct Equipped;
+
 #[derive(Component, Debug)]
 pub struct AmuletOfYala;
 
@@ -80,14 +93,29 @@ impl AmuletBundle {
 
 fn player_picks_up_items(
     mut commands: Commands,
-    player_query: Query<(Entity, &Position), (With<Player>, Changed<Position>)>,
-    item_query: Query<(Entity, &Position), With<Item>>,
+    mut player_query: Query<(Entity, &Position, &mut Gold), (With<Player>, Changed<Position>)>,
+    item_query: Query<(Entity, &Name, &Position, Option<&Gold>), (With<Item>, Without<Player>)>,
+    mut log_msgs: EventWriter<LogMessage>,
+    mut picked_up_msgs: EventWriter<PickedUpItem>,
 ) {
-    if let Some((player_entity, player_pos)) = player_query.iter().next() {
-        for (item_entity, _) in item_query
+    if let Some((player_entity, player_pos, mut player_gold)) = player_query.iter_mut().next() {
+        for (item_entity, name, _, gold) in item_query
             .iter()
-            .filter(|(_, item_pos)| *item_pos == player_pos)
+            .filter(|(_, _, item_pos, _)| *item_pos == player_pos)
         {
+            picked_up_msgs.send(PickedUpItem {
+                entity: player_entity,
+            });
+
+            // Gold goes straight into the purse rather than the inventory
+            if let Some(&Gold(amount)) = gold {
+                log_msgs.send(LogMessage::item(format!("You pick up {} gold", amount)));
+                player_gold.0 += amount;
+                commands.entity(item_entity).despawn();
+                continue;
+            }
+
+            log_msgs.send(LogMessage::item(format!("You pick up the {}", name)));
             commands
                 .entity(item_entity)
                 .remove::<Position>()
@@ -99,13 +127,72 @@ fn player_picks_up_items(
 
 fn consume_used_items(
     mut commands: Commands,
-    item_query: Query<(Entity, &CarriedBy, &Effects), Added<Used>>,
+    item_query: Query<(Entity, &Name, &CarriedBy, &Effects), (Added<Used>, Without<Equipment>)>,
+    player_query: Query<(), With<Player>>,
+    mut log_msgs: EventWriter<LogMessage>,
 ) {
-    for (item, &CarriedBy(carrier), effects) in item_query.iter() {
+    for (item, name, &CarriedBy(carrier), effects) in item_query.iter() {
+        // Keys are kept, as they are only of use when walking into a locked door
+        if effects
+            .0
+            .iter()
+            .any(|effect| matches!(effect, EffectData::Unlock(_)))
+        {
+            if player_query.get(carrier).is_ok() {
+                log_msgs.send(LogMessage::item(format!(
+                    "The {} unlocks a door when you walk into it",
+                    name
+                )));
+            }
+            commands.entity(item).remove::<Used>();
+            continue;
+        }
+
+        if player_query.get(carrier).is_ok() {
+            log_msgs.send(LogMessage::item(format!("You use the {}", name)));
+        }
         let mut carrier_commands = commands.entity(carrier);
         for effect in &effects.0 {
-            apply_effect_to_mob(&mut carrier_commands, &effect);
+            apply_effect_to_mob(&mut carrier_commands, effect);
         }
         commands.entity(item).despawn();
     }
 }
+
+// Using a piece of equipment puts it on, or takes it off again if it is already worn
+fn equip_used_items(
+    mut commands: Commands,
+    item_query: Query<(Entity, &Name, &CarriedBy, &Equipment, Option<&Equipped>), Added<Used>>,
+    equipped_query: Query<(Entity, &Name, &CarriedBy, &Equipment), With<Equipped>>,
+    player_query: Query<(), With<Player>>,
+    mut log_msgs: EventWriter<LogMessage>,
+) {
+    for (item, name, &CarriedBy(carrier), Equipment(equipment), equipped) in item_query.iter() {
+        let is_player = player_query.get(carrier).is_ok();
+        commands.entity(item).remove::<Used>();
+
+        if equipped.is_some() {
+            commands.entity(item).remove::<Equipped>();
+            if is_player {
+                log_msgs.send(LogMessage::item(format!("You take off the {}", name)));
+            }
+            continue;
+        }
+
+        // Only one item may be worn in each slot
+        for (other, other_name, _, _) in equipped_query.iter().filter(
+            |(_, _, &CarriedBy(other_carrier), Equipment(other_equipment))| {
+                other_carrier == carrier && other_equipment.slot == equipment.slot
+            },
+        ) {
+            commands.entity(other).remove::<Equipped>();
+            if is_player {
+                log_msgs.send(LogMessage::item(format!("You take off the {}", other_name)));
+            }
+        }
+        commands.entity(item).insert(Equipped);
+        if is_player {
+            log_msgs.send(LogMessage::item(format!("You put on the {}", name)));
+        }
+    }
+}
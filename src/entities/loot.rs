@@ -0,0 +1,97 @@
+use super::spawning::spawn_item;
+use crate::prelude::*;
+use rand::seq::SliceRandom;
+
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(CoreStage::Last, drop_loot);
+    }
+}
+
+// Name of the loot table rolled on when the mob dies, if any
+#[derive(Clone, Component, Debug, Default)]
+pub struct LootTable(pub Option<String>);
+
+// Coins carried by a mob, or lying in a pile on the floor
+#[derive(Clone, Copy, Component, Debug, Default)]
+pub struct Gold(pub u32);
+
+pub(super) fn roll_loot<'a>(
+    table_name: &str,
+    loot_tables: &'a Assets<LootTableData>,
+    rng: &mut GameRng,
+) -> Option<&'a LootDrop> {
+    let table = loot_tables
+        .iter()
+        .find(|(_, table)| table.name == table_name);
+    match table {
+        Some((_, table)) => table
+            .entries
+            .choose_weighted(rng, |entry| entry.weight)
+            .ok()
+            .map(|entry| &entry.drop),
+        None => {
+            warn!("Unknown loot table {}", table_name);
+            None
+        }
+    }
+}
+
+pub(super) fn spawn_loot(
+    commands: &mut Commands,
+    drop: &LootDrop,
+    position: Position,
+    item_data: &Assets<ItemData>,
+    asset_server: &AssetServer,
+    rng: &mut GameRng,
+) {
+    match drop {
+        LootDrop::Nothing => {}
+        LootDrop::Gold(dice) => {
+            let amount = dice.roll(rng).max(1) as u32;
+            commands
+                .spawn_bundle(ItemBundle::new(
+                    "Gold",
+                    position,
+                    asset_server.load("images/items/gold.png"),
+                ))
+                .insert(Gold(amount));
+        }
+        LootDrop::Item(name) => match item_data.iter().find(|(_, item)| &item.name == name) {
+            Some((_, item)) => spawn_item(commands, item, position, asset_server),
+            None => warn!("Unknown item {} in loot table", name),
+        },
+    }
+}
+
+fn drop_loot(
+    mut commands: Commands,
+    mob_query: Query<(&Health, &Position, &LootTable), Changed<Health>>,
+    loot_tables: Res<Assets<LootTableData>>,
+    item_data: Res<Assets<ItemData>>,
+    asset_server: Res<AssetServer>,
+    mut rng: ResMut<GameRng>,
+) {
+    for (_, &position, table_name) in mob_query
+        .iter()
+        .filter(|(health, ..)| health.is_dead())
+        .filter_map(|(health, position, LootTable(table_name))| {
+            table_name
+                .as_ref()
+                .map(|table_name| (health, position, table_name))
+        })
+    {
+        if let Some(drop) = roll_loot(table_name, &loot_tables, rng.as_mut()) {
+            spawn_loot(
+                &mut commands,
+                drop,
+                position,
+                &item_data,
+                &asset_server,
+                rng.as_mut(),
+            );
+        }
+    }
+}
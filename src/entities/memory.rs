@@ -0,0 +1,136 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+
+const GHOST_COLOR: Color = Color::rgba(0.5, 0.5, 0.5, 0.6);
+
+pub struct MapMemoryPlugin;
+
+impl Plugin for MapMemoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            update_map_memory.label("update_map_memory"),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            sync_ghosts.after("update_map_memory"),
+        );
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Glyph {
+    Atlas(usize),
+    Image(Handle<Image>),
+}
+
+#[derive(Clone, Debug)]
+pub struct RememberedEntity {
+    pub position: Position,
+    pub glyph: Glyph,
+}
+
+#[derive(Clone, Component, Debug, Default)]
+pub struct MapMemory {
+    pub entities: HashMap<Entity, RememberedEntity>,
+}
+
+#[derive(Component, Debug)]
+struct Ghost {
+    of: Entity,
+    position: Position,
+}
+
+fn update_map_memory(
+    mut player_query: Query<(&FieldOfView, &mut MapMemory), With<Player>>,
+    entity_query: Query<
+        (
+            Entity,
+            &Position,
+            Option<&TextureAtlasSprite>,
+            Option<&Handle<Image>>,
+        ),
+        Or<(With<Hostile>, With<Item>)>,
+    >,
+) {
+    for (fov, mut memory) in player_query.iter_mut() {
+        // Whatever is in view is remembered afresh; this also forgets entities which are no
+        // longer where they were last seen
+        memory
+            .entities
+            .retain(|_, remembered| !fov.can_see(remembered.position));
+
+        for (entity, &position, sprite, image) in entity_query.iter() {
+            if !fov.can_see(position) {
+                continue;
+            }
+            let glyph = match (sprite, image) {
+                (Some(sprite), _) => Glyph::Atlas(sprite.index),
+                (None, Some(image)) => Glyph::Image(image.clone()),
+                (None, None) => continue,
+            };
+            memory
+                .entities
+                .insert(entity, RememberedEntity { position, glyph });
+        }
+    }
+}
+
+fn sync_ghosts(
+    mut commands: Commands,
+    player_query: Query<(&FieldOfView, &MapMemory), With<Player>>,
+    ghost_query: Query<(Entity, &Ghost)>,
+    texture_atlas: Res<DefaultTextureAtlas>,
+) {
+    // Remembered entities which are out of view are represented by ghosts
+    let mut pending_ghosts: HashMap<Entity, &RememberedEntity> = player_query
+        .iter()
+        .flat_map(|(fov, memory)| {
+            memory
+                .entities
+                .iter()
+                .filter(|(_, remembered)| !fov.can_see(remembered.position))
+                .map(|(&entity, remembered)| (entity, remembered))
+        })
+        .collect();
+
+    for (ghost_entity, ghost) in ghost_query.iter() {
+        match pending_ghosts.get(&ghost.of) {
+            Some(remembered) if remembered.position == ghost.position => {
+                pending_ghosts.remove(&ghost.of);
+            }
+            _ => commands.entity(ghost_entity).despawn(),
+        }
+    }
+
+    let DefaultTextureAtlas(atlas_handle) = texture_atlas.as_ref();
+    for (entity, remembered) in pending_ghosts {
+        let transform = Transform::from_translation(
+            tile_center(&remembered.position) - Vec3::new(0.0, 0.0, 0.1),
+        );
+        let mut ghost_commands = match &remembered.glyph {
+            Glyph::Atlas(index) => commands.spawn_bundle(SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    color: GHOST_COLOR,
+                    ..TextureAtlasSprite::new(*index)
+                },
+                texture_atlas: atlas_handle.clone(),
+                transform,
+                ..default()
+            }),
+            Glyph::Image(image) => commands.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: GHOST_COLOR,
+                    ..default()
+                },
+                texture: image.clone(),
+                transform,
+                ..default()
+            }),
+        };
+        ghost_commands.insert(Ghost {
+            of: entity,
+            position: remembered.position,
+        });
+    }
+}
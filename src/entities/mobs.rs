@@ -1,4 +1,7 @@
+use crate::data::Direction;
 use crate::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
 
 pub struct MobPlugin;
 
@@ -18,7 +21,11 @@ struct MobBundle {
     mob: Mob,
     position: Position,
     health: Health,
+    combat: CombatStats,
     fov: FieldOfView,
+    speed: Speed,
+    energy: Energy,
+    status_effects: StatusEffects,
     #[bundle]
     sprite_sheet_bundle: SpriteSheetBundle,
 }
@@ -27,7 +34,9 @@ impl MobBundle {
     pub fn new(
         position: Position,
         health: Health,
+        combat: CombatStats,
         fov_radius: i32,
+        speed: i32,
         texture_index: usize,
         texture_atlas: &DefaultTextureAtlas,
     ) -> Self {
@@ -37,7 +46,11 @@ impl MobBundle {
             mob: Mob,
             position,
             health,
+            combat,
             fov: FieldOfView::new(fov_radius),
+            speed: Speed(speed),
+            energy: Energy::default(),
+            status_effects: StatusEffects::default(),
             sprite_sheet_bundle: SpriteSheetBundle {
                 transform: Transform::from_translation(world_pos),
                 texture_atlas: atlas_handle.clone(),
@@ -51,15 +64,37 @@ impl MobBundle {
 #[derive(Bundle)]
 pub struct PlayerBundle {
     player: Player,
+    memory: MapMemory,
+    experience: Experience,
+    gold: Gold,
     #[bundle]
     mob_bundle: MobBundle,
 }
 
 impl PlayerBundle {
-    pub fn new(position: Position, texture_atlas: &DefaultTextureAtlas) -> Self {
+    pub fn new(
+        position: Position,
+        player: &PlayerData,
+        texture_atlas: &DefaultTextureAtlas,
+    ) -> Self {
+        let mut mob_bundle = MobBundle::new(
+            position,
+            Health::new(player.health),
+            player.combat.clone(),
+            player.fov_radius,
+            player.speed,
+            player.glyph,
+            texture_atlas,
+        );
+        // The player gets to make the first move
+        mob_bundle.energy = Energy(ACTION_COST);
+
         PlayerBundle {
             player: Player,
-            mob_bundle: MobBundle::new(position, Health::new(10), 8, 64, texture_atlas),
+            memory: MapMemory::default(),
+            experience: Experience::default(),
+            gold: Gold::default(),
+            mob_bundle,
         }
     }
 }
@@ -67,17 +102,29 @@ impl PlayerBundle {
 #[derive(Bundle)]
 pub struct HostileMobBundle {
     hostile: Hostile,
-    chasing: ChasingPlayer,
+    name: Name,
+    reward: ExperienceReward,
+    loot: LootTable,
     #[bundle]
     mob_bundle: MobBundle,
 }
 
 impl HostileMobBundle {
-    pub fn new(position: Position, texture_atlas: &DefaultTextureAtlas) -> Self {
+    pub fn new(position: Position, mob: &MobData, texture_atlas: &DefaultTextureAtlas) -> Self {
         HostileMobBundle {
             hostile: Hostile,
-            chasing: ChasingPlayer,
-            mob_bundle: MobBundle::new(position, Health::new(1), 6, 103, texture_atlas),
+            name: Name::new(mob.name.clone()),
+            reward: ExperienceReward(mob.xp),
+            loot: LootTable(mob.loot.clone()),
+            mob_bundle: MobBundle::new(
+                position,
+                Health::new(mob.health),
+                mob.combat.clone(),
+                mob.fov_radius,
+                mob.speed,
+                mob.glyph,
+                texture_atlas,
+            ),
         }
     }
 }
@@ -86,22 +133,68 @@ fn movement(
     mut movement_msgs: EventReader<WantsToMove>,
     mut commands: Commands,
     mut tile_map_query: TileMapQuery,
-    mob_query: Query<(Entity, &Position), With<Mob>>,
+    mob_query: Query<(Entity, &Position, Option<&Swimmer>, Option<&StatusEffects>), With<Mob>>,
+    mut energy_query: Query<&mut Energy>,
     mut attack_msgs: EventWriter<WantsToAttack>,
+    mut open_door_msgs: EventWriter<WantsToOpenDoor>,
+    mut rng: ResMut<GameRng>,
 ) {
     for &WantsToMove {
         entity,
         destination,
     } in movement_msgs.iter()
     {
-        if tile_map_query.can_enter_tile(&destination) {
-            if let Some((mob, _)) = mob_query.iter().find(|(_, pos)| **pos == destination) {
+        let (position, can_swim, confused) = match mob_query.get(entity) {
+            Ok((_, &position, swimmer, status_effects)) => (
+                position,
+                swimmer.is_some(),
+                status_effects
+                    .map(|status_effects| status_effects.has(StatusEffectKind::Confusion))
+                    .unwrap_or(false),
+            ),
+            Err(_) => continue,
+        };
+
+        // Confused mobs stagger off in a random direction half of the time
+        let destination = if confused && rng.gen_bool(0.5) {
+            let direction = [
+                Direction::North,
+                Direction::South,
+                Direction::West,
+                Direction::East,
+            ]
+            .choose(rng.as_mut())
+            .unwrap();
+            Position(position.0 + direction.delta())
+        } else {
+            destination
+        };
+
+        if !tile_map_query.can_reach(&position, &destination, can_swim) {
+            continue;
+        }
+
+        if let Some(TileType::DoorClosed | TileType::DoorLocked(_)) =
+            tile_map_query.tile_type(&destination)
+        {
+            // Bumping into a closed door opens it
+            open_door_msgs.send(WantsToOpenDoor {
+                entity,
+                position: destination,
+            });
+        } else if let Some(movement_cost) = tile_map_query.movement_cost(&destination, can_swim) {
+            if let Some((mob, ..)) = mob_query.iter().find(|(_, pos, ..)| **pos == destination) {
                 attack_msgs.send(WantsToAttack {
                     attacker: entity,
                     victim: mob,
                 });
             } else {
                 commands.entity(entity).insert(destination);
+
+                // Wading through difficult terrain takes extra time
+                if let Ok(mut energy) = energy_query.get_mut(entity) {
+                    energy.0 -= ((movement_cost - 1.0) * ACTION_COST as f32) as i32;
+                }
             }
         }
     }
@@ -2,23 +2,37 @@ mod ai;
 mod avatar;
 mod combat;
 mod effects;
+mod energy;
+mod experience;
 mod fov;
+mod hazards;
 mod items;
+mod loot;
+mod memory;
 mod mobs;
 mod spawning;
 
 use crate::prelude::*;
 use bevy::app::PluginGroupBuilder;
+use serde::{Deserialize, Serialize};
 
 use ai::*;
 use avatar::*;
 use combat::*;
 use fov::*;
+use hazards::*;
+use loot::*;
+use memory::*;
 use mobs::*;
 
+pub use ai::apply_behavior_to_mob;
 pub use effects::*;
+pub use energy::*;
+pub use experience::*;
 pub use fov::FieldOfView;
 pub use items::*;
+pub use loot::{Gold, LootTable};
+pub use memory::{Glyph, MapMemory, RememberedEntity};
 pub use mobs::{HostileMobBundle, PlayerBundle};
 use spawning::*;
 
@@ -33,7 +47,12 @@ impl PluginGroup for EntityPlugins {
             .add(FieldOfViewPlugin)
             .add(AvatarPlugin)
             .add(ItemPlugin)
+            .add(LootPlugin)
+            .add(MapMemoryPlugin)
             .add(EffectsPlugin)
+            .add(EnergyPlugin)
+            .add(ExperiencePlugin)
+            .add(HazardPlugin)
             .add(EntitySpawningPlugin);
     }
 }
@@ -47,7 +66,7 @@ pub struct Hostile;
 #[derive(Component, Debug)]
 pub struct Mob;
 
-#[derive(Component, Debug)]
+#[derive(Clone, Component, Debug, Deserialize, Serialize)]
 pub struct Health {
     current: u32,
     max: u32,
@@ -73,6 +92,12 @@ impl Health {
         health_restored
     }
 
+    // Raises the maximum and heals by the same amount
+    pub fn raise_max(&mut self, amount: u32) {
+        self.max += amount;
+        self.current += amount;
+    }
+
     pub fn is_dead(&self) -> bool {
         self.current == 0
     }
@@ -82,9 +107,21 @@ impl Health {
     }
 }
 
+#[derive(Clone, Component, Debug, Deserialize, Serialize)]
+pub struct CombatStats {
+    pub attack: i32,
+    pub defense: i32,
+    pub damage: Dice,
+    pub critical_chance: f32,
+}
+
 #[derive(Component, Debug)]
 pub struct ChasingPlayer;
 
+// Can cross deep water and wades through shallow water without slowing down
+#[derive(Component, Debug)]
+pub struct Swimmer;
+
 #[derive(Debug)]
 pub struct WantsToMove {
     pub entity: Entity,
@@ -1,66 +1,43 @@
+use super::loot::{roll_loot, spawn_loot};
 use crate::prelude::*;
 
 pub struct EntitySpawningPlugin;
 
 impl Plugin for EntitySpawningPlugin {
     fn build(&self, app: &mut App) {
-        app.add_exit_system(TurnState::NewGame, spawn_entities);
+        app.add_exit_system(TurnState::NewGame, spawn_entities)
+            .add_exit_system(TurnState::NextLevel, spawn_entities);
     }
 }
 
-fn find_start_positions(
-    spawnable_locations: &[Position],
-    tile_map_query: TileMapQuery,
-) -> (Position, Position) {
-    let map_adapter = MapAdapter::new(tile_map_query);
-
-    let center = map_adapter.dimensions() / 2;
-
-    let player_start = spawnable_locations
-        .into_iter()
-        .min_by_key(|&&p| DistanceAlg::PythagorasSquared.distance2d(center, p.into()) as i64)
-        .unwrap()
-        .clone();
-
-    let dijkstra_map = DijkstraMap::new(
-        MAP_WIDTH,
-        MAP_HEIGHT,
-        &[map_adapter.point2d_to_index(player_start.into())],
-        &map_adapter,
-        1024.0,
-    );
-
-    const UNREACHABLE: &f32 = &f32::MAX;
-    let amulet_start = map_adapter
-        .index_to_point2d(
-            dijkstra_map
-                .map
-                .iter()
-                .enumerate()
-                .filter(|(_, dist)| *dist < UNREACHABLE)
-                .max_by(|(_, lhs_dist), (_, rhs_dist)| lhs_dist.partial_cmp(rhs_dist).unwrap())
-                .unwrap()
-                .0,
-        )
-        .into();
-
-    (player_start, amulet_start)
-}
-
 fn spawn_entities(
     mut commands: Commands,
     tile_map_query: TileMapQuery,
-    entity_query: Query<Entity, Or<(With<Mob>, With<Item>)>>,
+    turn_state: Res<CurrentState<TurnState>>,
+    start_positions: Res<StartPositions>,
+    entity_query: Query<(Entity, Option<&Player>, Option<&CarriedBy>), Or<(With<Mob>, With<Item>)>>,
     texture_atlas: Res<DefaultTextureAtlas>,
     item_data: Res<Assets<ItemData>>,
+    mob_data: Res<Assets<MobData>>,
+    player_data: Res<PlayerData>,
+    loot_tables: Res<Assets<LootTableData>>,
+    current_biome: Res<CurrentBiome>,
     asset_server: Res<AssetServer>,
+    mut rng: ResMut<GameRng>,
 ) {
-    const NUM_MONSTERS: usize = 50;
     const MIN_DISTANCE: f32 = 10f32;
 
-    // Despawn all entities in case of game reset
-    for entity in entity_query.iter() {
-        commands.entity(entity).despawn();
+    // Despawn all entities in case of game reset, but keep the player and their
+    // inventory when descending to the next level
+    let CurrentState(current_turn_state) = *turn_state;
+    let keep_player = current_turn_state == TurnState::NextLevel;
+    let mut player_entity = None;
+    for (entity, player, carried_by) in entity_query.iter() {
+        if keep_player && player.is_some() {
+            player_entity = Some(entity);
+        } else if !keep_player || carried_by.is_none() {
+            commands.entity(entity).despawn();
+        }
     }
 
     // Determine spawnable locations
@@ -71,50 +48,164 @@ fn spawn_entities(
         .map(|(&tile_pos, _)| tile_pos.into())
         .collect();
 
-    // Spawn player
-    let (player_start, amulet_start) = find_start_positions(&spawnable_locations, tile_map_query);
-    commands.spawn_bundle(PlayerBundle::new(player_start, texture_atlas.as_ref()));
+    // Spawn player or move them to the start of the new level
+    let player_start = start_positions.player;
+    if let Some(player_entity) = player_entity {
+        commands
+            .entity(player_entity)
+            .insert(player_start)
+            .insert(MapMemory::default())
+            .insert(Transform::from_translation(tile_center(&player_start)))
+            .remove::<Travelling>();
+    } else {
+        commands.spawn_bundle(PlayerBundle::new(
+            player_start,
+            player_data.as_ref(),
+            texture_atlas.as_ref(),
+        ));
+    }
 
-    // Spawn the amulet
-    commands.spawn_bundle(AmuletBundle::new(amulet_start, texture_atlas.as_ref()));
+    // Spawn the amulet on the final level
+    if let Some(amulet_start) = start_positions.amulet {
+        commands.spawn_bundle(AmuletBundle::new(amulet_start, texture_atlas.as_ref()));
+    }
 
-    // Exclude the vicinity of the player from the spawnable set
+    // Populate the vaults stamped into the map and hand out the keys to its locked doors
+    let vault_items = start_positions.items.iter().filter_map(|(position, name)| {
+        let item = item_data
+            .iter()
+            .map(|(_, item)| item)
+            .find(|item| &item.name == name);
+        if item.is_none() {
+            warn!("Unknown item {} in vault", name);
+        }
+        item.map(|item| (*position, item))
+    });
+    let keys = start_positions
+        .keys
+        .iter()
+        .filter_map(|&(position, key_id)| {
+            item_data
+                .iter()
+                .map(|(_, item)| item)
+                .filter(|item| item.effects.contains(&EffectData::Unlock(key_id)))
+                .min_by(|lhs, rhs| lhs.name.cmp(&rhs.name))
+                .map(|item| (position, item))
+        });
+    for (position, item) in vault_items.chain(keys) {
+        spawn_item(&mut commands, item, position, asset_server.as_ref());
+    }
+    for (position, name) in &start_positions.mobs {
+        match mob_data
+            .iter()
+            .find(|(_, mob)| &mob.name == name)
+            .map(|(_, mob)| mob)
+        {
+            Some(mob) => {
+                let mut mob_commands = commands.spawn_bundle(HostileMobBundle::new(
+                    *position,
+                    mob,
+                    texture_atlas.as_ref(),
+                ));
+                for behavior in &mob.behaviors {
+                    apply_behavior_to_mob(&mut mob_commands, behavior);
+                }
+            }
+            None => warn!("Unknown mob {} in vault", name),
+        }
+    }
+
+    // Exclude the vicinity of the player and the fixed spawns from the spawnable set
     let mut spawnable_locations: Vec<_> = spawnable_locations
         .into_iter()
         .filter(|&p: &Position| {
             DistanceAlg::Pythagoras.distance2d(p.into(), player_start.into()) > MIN_DISTANCE
         })
+        .filter(|p| {
+            !start_positions
+                .mobs
+                .iter()
+                .chain(start_positions.items.iter())
+                .map(|(position, _)| position)
+                .chain(start_positions.keys.iter().map(|(position, _)| position))
+                .any(|position| position == p)
+        })
         .collect();
 
     use rand::prelude::*;
-    let mut rng = thread_rng();
-    spawnable_locations.shuffle(&mut rng);
+    spawnable_locations.shuffle(rng.as_mut());
 
     let location_count = spawnable_locations.len();
     let mut spawnable_locations_iter = spawnable_locations.into_iter();
 
-    // Spawn items
-    for (item, spawn_location) in item_data.iter().flat_map(|(_, item)| {
+    // Asset iteration order is unspecified, so sort by name to keep the placement reproducible
+    let mut mob_data: Vec<_> = mob_data.iter().map(|(_, mob)| mob).collect();
+    mob_data.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+
+    // Spawn items, drawing them from the biome's loot table if it has one
+    let CurrentBiome(biome) = current_biome.as_ref();
+    match biome.as_ref().and_then(|biome| biome.loot.as_ref()) {
+        Some(loot) => {
+            for spawn_location in spawnable_locations_iter.by_ref().take(loot.rolls) {
+                if let Some(drop) = roll_loot(&loot.table, &loot_tables, rng.as_mut()) {
+                    spawn_loot(
+                        &mut commands,
+                        drop,
+                        spawn_location,
+                        &item_data,
+                        &asset_server,
+                        rng.as_mut(),
+                    );
+                }
+            }
+        }
+        None => {
+            let mut item_data: Vec<_> = item_data.iter().map(|(_, item)| item).collect();
+            item_data.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+            for (item, spawn_location) in item_data.into_iter().flat_map(|item| {
+                spawnable_locations_iter
+                    .by_ref()
+                    .take((item.frequency * location_count as f32) as usize)
+                    .map(|loc| (item, loc))
+                    .collect::<Vec<_>>()
+            }) {
+                spawn_item(&mut commands, item, spawn_location, asset_server.as_ref());
+            }
+        }
+    }
+
+    // Spawn monsters
+    for (mob, spawn_location) in mob_data.into_iter().flat_map(|mob| {
         spawnable_locations_iter
             .by_ref()
-            .take((item.frequency * location_count as f32) as usize)
-            .map(|loc| (item, loc))
+            .take((mob.frequency * location_count as f32) as usize)
+            .map(|loc| (mob, loc))
             .collect::<Vec<_>>()
     }) {
-        commands
-            .spawn_bundle(ItemBundle::new(
-                &item.name,
-                spawn_location,
-                asset_server.load(item.icon.as_str()),
-            ))
-            .insert(Effects(item.effects.clone()));
-    }
-
-    // Spawn monsters
-    for spawn_location in spawnable_locations_iter.take(NUM_MONSTERS) {
-        commands.spawn_bundle(HostileMobBundle::new(
+        let mut mob_commands = commands.spawn_bundle(HostileMobBundle::new(
             spawn_location,
+            mob,
             texture_atlas.as_ref(),
         ));
+        for behavior in &mob.behaviors {
+            apply_behavior_to_mob(&mut mob_commands, behavior);
+        }
+    }
+}
+
+pub(super) fn spawn_item(
+    commands: &mut Commands,
+    item: &ItemData,
+    position: Position,
+    asset_server: &AssetServer,
+) {
+    let mut item_commands = commands.spawn_bundle(ItemBundle::new(
+        &item.name,
+        position,
+        asset_server.load(item.icon.as_str()),
+    ));
+    item_commands.insert(Effects(item.effects.clone()));
+    if let Some(equipment) = &item.equipment {
+        item_commands.insert(Equipment(equipment.clone()));
     }
 }
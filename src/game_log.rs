@@ -0,0 +1,125 @@
+use crate::prelude::*;
+use std::collections::VecDeque;
+
+pub struct GameLogPlugin {
+    pub history_length: usize,
+}
+
+impl Default for GameLogPlugin {
+    fn default() -> Self {
+        GameLogPlugin {
+            history_length: 100,
+        }
+    }
+}
+
+impl Plugin for GameLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LogMessage>()
+            .insert_resource(GameLog::new(self.history_length))
+            .add_enter_system(TurnState::NewGame, clear_game_log)
+            .add_system_to_stage(CoreStage::PostUpdate, record_log_messages);
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LogCategory {
+    Info,
+    Combat,
+    Item,
+}
+
+#[derive(Clone, Debug)]
+pub struct LogMessage {
+    pub category: LogCategory,
+    pub text: String,
+}
+
+impl LogMessage {
+    pub fn info(text: impl Into<String>) -> Self {
+        LogMessage {
+            category: LogCategory::Info,
+            text: text.into(),
+        }
+    }
+
+    pub fn combat(text: impl Into<String>) -> Self {
+        LogMessage {
+            category: LogCategory::Combat,
+            text: text.into(),
+        }
+    }
+
+    pub fn item(text: impl Into<String>) -> Self {
+        LogMessage {
+            category: LogCategory::Item,
+            text: text.into(),
+        }
+    }
+}
+
+pub struct GameLog {
+    history_length: usize,
+    messages: VecDeque<LogMessage>,
+}
+
+impl GameLog {
+    pub fn new(history_length: usize) -> Self {
+        GameLog {
+            history_length,
+            messages: VecDeque::with_capacity(history_length),
+        }
+    }
+
+    pub fn push(&mut self, message: LogMessage) {
+        self.messages.push_back(message);
+        while self.messages.len() > self.history_length {
+            self.messages.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    pub fn messages(&self) -> impl DoubleEndedIterator<Item = &LogMessage> + ExactSizeIterator {
+        self.messages.iter()
+    }
+}
+
+fn clear_game_log(mut game_log: ResMut<GameLog>) {
+    game_log.clear();
+}
+
+fn record_log_messages(mut log_msgs: EventReader<LogMessage>, mut game_log: ResMut<GameLog>) {
+    for message in log_msgs.iter() {
+        game_log.push(message.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(game_log: &GameLog) -> Vec<&str> {
+        game_log.messages().map(|msg| msg.text.as_str()).collect()
+    }
+
+    #[test]
+    fn push_drops_the_oldest_messages() {
+        let mut game_log = GameLog::new(2);
+        game_log.push(LogMessage::info("first"));
+        game_log.push(LogMessage::info("second"));
+        assert_eq!(texts(&game_log), ["first", "second"]);
+
+        game_log.push(LogMessage::info("third"));
+        assert_eq!(texts(&game_log), ["second", "third"]);
+    }
+
+    #[test]
+    fn push_without_history_keeps_nothing() {
+        let mut game_log = GameLog::new(0);
+        game_log.push(LogMessage::info("lost"));
+        assert_eq!(game_log.messages().len(), 0);
+    }
+}
@@ -1,8 +1,19 @@
+// Bevy systems routinely take many parameters and nested query types, and the Bundle derive
+// forgets values which don't implement Drop
+#![allow(
+    clippy::type_complexity,
+    clippy::too_many_arguments,
+    clippy::forget_non_drop
+)]
+
 mod camera;
 mod data;
 mod entities;
+mod game_log;
 mod map;
 mod player_input;
+mod rng;
+mod save;
 mod texture;
 mod turn_state;
 mod ui;
@@ -11,8 +22,11 @@ mod prelude {
     pub use crate::camera::*;
     pub use crate::data::*;
     pub use crate::entities::*;
+    pub use crate::game_log::*;
     pub use crate::map::*;
     pub use crate::player_input::*;
+    pub use crate::rng::*;
+    pub use crate::save::*;
     pub use crate::texture::*;
     pub use crate::turn_state::*;
     pub use crate::ui::*;
@@ -44,13 +58,16 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugins(DefaultNavigationPlugins)
         .add_plugin(TurnStatePlugin)
+        .add_plugin(GameLogPlugin::default())
+        .add_plugin(GameRngPlugin)
         .add_plugins(DataPlugins)
         .add_plugins(EntityPlugins)
         .add_plugins(UiPlugins)
-        .add_plugin(FrameTimeDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
         .add_plugin(PlayerInputPlugin)
         .add_plugin(MapPlugin)
         .add_plugin(CameraPlugin)
+        .add_plugin(SavePlugin)
         .add_startup_system_to_stage(StartupStage::PreStartup, build_texture_atlases)
         .run();
 }
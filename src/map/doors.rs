@@ -0,0 +1,109 @@
+use crate::prelude::*;
+
+pub struct DoorPlugin;
+
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WantsToOpenDoor>()
+            .add_event::<WantsToCloseDoor>()
+            .add_system(open_doors)
+            .add_system(close_doors);
+    }
+}
+
+#[derive(Debug)]
+pub struct WantsToOpenDoor {
+    pub entity: Entity,
+    pub position: Position,
+}
+
+#[derive(Debug)]
+pub struct WantsToCloseDoor {
+    pub entity: Entity,
+    pub position: Position,
+}
+
+fn open_doors(
+    mut open_msgs: EventReader<WantsToOpenDoor>,
+    mut log_msgs: EventWriter<LogMessage>,
+    mut map_query: MapQuery,
+    mut tile_query: Query<&mut TileType>,
+    player_query: Query<(), With<Player>>,
+    key_query: Query<(&Name, &CarriedBy, &Effects), With<Item>>,
+) {
+    for &WantsToOpenDoor { entity, position } in open_msgs.iter() {
+        let is_player = player_query.get(entity).is_ok();
+        if let Some(mut tile_type) = door_at(&mut map_query, &mut tile_query, position) {
+            match *tile_type {
+                TileType::DoorClosed => {
+                    *tile_type = TileType::DoorOpen;
+                    if is_player {
+                        log_msgs.send(LogMessage::info("You open the door"));
+                    }
+                }
+                TileType::DoorLocked(key_id) => {
+                    let key = key_query
+                        .iter()
+                        .filter(|(_, &CarriedBy(carrier), _)| carrier == entity)
+                        .find(|(_, _, Effects(effects))| {
+                            effects.contains(&EffectData::Unlock(key_id))
+                        });
+                    match key {
+                        Some((name, ..)) => {
+                            *tile_type = TileType::DoorOpen;
+                            if is_player {
+                                log_msgs.send(LogMessage::info(format!(
+                                    "You unlock the door with the {}",
+                                    name
+                                )));
+                            }
+                        }
+                        None if is_player => {
+                            log_msgs.send(LogMessage::info("The door is locked"));
+                        }
+                        None => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn close_doors(
+    mut close_msgs: EventReader<WantsToCloseDoor>,
+    mut log_msgs: EventWriter<LogMessage>,
+    mut map_query: MapQuery,
+    mut tile_query: Query<&mut TileType>,
+    player_query: Query<(), With<Player>>,
+    occupant_query: Query<&Position, Or<(With<Mob>, With<Item>)>>,
+) {
+    for &WantsToCloseDoor { entity, position } in close_msgs.iter() {
+        let is_player = player_query.get(entity).is_ok();
+        if occupant_query.iter().any(|&pos| pos == position) {
+            if is_player {
+                log_msgs.send(LogMessage::info("Something is in the way of the door"));
+            }
+            continue;
+        }
+        if let Some(mut tile_type) = door_at(&mut map_query, &mut tile_query, position) {
+            if *tile_type == TileType::DoorOpen {
+                *tile_type = TileType::DoorClosed;
+                if is_player {
+                    log_msgs.send(LogMessage::info("You close the door"));
+                }
+            }
+        }
+    }
+}
+
+fn door_at<'a>(
+    map_query: &mut MapQuery,
+    tile_query: &'a mut Query<&mut TileType>,
+    position: Position,
+) -> Option<Mut<'a, TileType>> {
+    let tile_entity = map_query
+        .get_tile_entity(position.try_into().ok()?, MAP_ID, MAP_LAYER_ID)
+        .ok()?;
+    tile_query.get_mut(tile_entity).ok()
+}
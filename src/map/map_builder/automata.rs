@@ -3,18 +3,28 @@ use itertools::Itertools;
 
 use super::MapArchitect;
 use crate::map::map_builder::MapBuilder;
-use rand::{rngs::ThreadRng, thread_rng, Rng};
+use rand::Rng;
 
-#[derive(Default)]
-pub struct CellularAutomataArchitect {}
+pub struct CellularAutomataArchitect {
+    fill_percentage: u32,
+    iterations: usize,
+}
 
 impl CellularAutomataArchitect {
-    fn random_noise_map(&mut self, rng: &mut ThreadRng, mb: &mut MapBuilder) {
+    pub fn new(fill_percentage: u32, iterations: usize) -> Self {
+        CellularAutomataArchitect {
+            fill_percentage,
+            iterations,
+        }
+    }
+
+    fn random_noise_map(&mut self, rng: &mut GameRng, mb: &mut MapBuilder) {
+        let fill_percentage = self.fill_percentage;
         mb.map_data.fill_with(|| {
-            if rng.gen_range(0..100) > 55 {
-                TileType::Floor
-            } else {
+            if rng.gen_range(0..100) < fill_percentage {
                 TileType::Wall
+            } else {
+                TileType::Floor
             }
         });
     }
@@ -32,7 +42,7 @@ impl CellularAutomataArchitect {
         let mut new_tiles = mb.map_data.clone();
 
         for (idx, new_tile) in new_tiles.iter_mut().enumerate() {
-            *new_tile = match self.count_neighbors(&mb.index_to_point2d(idx), &mb) {
+            *new_tile = match self.count_neighbors(&mb.index_to_point2d(idx), mb) {
                 0 => TileType::Wall,
                 n if (1..=4).contains(&n) => TileType::Floor,
                 _ => TileType::Wall,
@@ -44,12 +54,11 @@ impl CellularAutomataArchitect {
 }
 
 impl MapArchitect for CellularAutomataArchitect {
-    fn architect(&mut self) -> MapBuilder {
+    fn architect(&mut self, rng: &mut GameRng) -> MapBuilder {
         let mut mb = MapBuilder::default();
 
-        let mut rng = thread_rng();
-        self.random_noise_map(&mut rng, &mut mb);
-        for _ in 0..10 {
+        self.random_noise_map(rng, &mut mb);
+        for _ in 0..self.iterations {
             self.iteration(&mut mb);
         }
 
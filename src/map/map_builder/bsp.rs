@@ -0,0 +1,100 @@
+use super::*;
+use crate::prelude::*;
+use bracket_geometry::prelude::Rect;
+use rand::Rng;
+
+pub struct BspArchitect {
+    min_leaf_size: i32,
+    min_room_size: i32,
+    rooms: Vec<Rect>,
+}
+
+impl BspArchitect {
+    pub fn new(min_leaf_size: i32, min_room_size: i32) -> Self {
+        let min_room_size = min_room_size.max(1);
+        BspArchitect {
+            // Leaves need space for the smallest room plus a surrounding wall
+            min_leaf_size: min_leaf_size.max(min_room_size + 2),
+            min_room_size,
+            rooms: Vec::new(),
+        }
+    }
+
+    // Returns a room from the partition, through which the partition can be connected to its sibling
+    fn partition(&mut self, region: Rect, mb: &mut MapBuilder, rng: &mut GameRng) -> Rect {
+        let can_split_x = region.width() >= 2 * self.min_leaf_size;
+        let can_split_y = region.height() >= 2 * self.min_leaf_size;
+        let split_x = match (can_split_x, can_split_y) {
+            (false, false) => return self.place_room(region, mb, rng),
+            (true, true) => region.width() >= region.height(),
+            (can_split_x, _) => can_split_x,
+        };
+
+        let (first, second) = if split_x {
+            let split =
+                rng.gen_range(region.x1 + self.min_leaf_size..=region.x2 - self.min_leaf_size);
+            (
+                Rect::with_exact(region.x1, region.y1, split, region.y2),
+                Rect::with_exact(split, region.y1, region.x2, region.y2),
+            )
+        } else {
+            let split =
+                rng.gen_range(region.y1 + self.min_leaf_size..=region.y2 - self.min_leaf_size);
+            (
+                Rect::with_exact(region.x1, region.y1, region.x2, split),
+                Rect::with_exact(region.x1, split, region.x2, region.y2),
+            )
+        };
+
+        let first_room = self.partition(first, mb, rng);
+        let second_room = self.partition(second, mb, rng);
+        connect_rooms(&first_room, &second_room, mb, rng);
+
+        if rng.gen_range(0..2) == 0 {
+            first_room
+        } else {
+            second_room
+        }
+    }
+
+    fn place_room(&mut self, region: Rect, mb: &mut MapBuilder, rng: &mut GameRng) -> Rect {
+        let width = rng.gen_range(self.min_room_size..=region.width() - 2);
+        let height = rng.gen_range(self.min_room_size..=region.height() - 2);
+        let room = Rect::with_size(
+            rng.gen_range(region.x1 + 1..=region.x2 - 1 - width),
+            rng.gen_range(region.y1 + 1..=region.y2 - 1 - height),
+            width,
+            height,
+        );
+        room.for_each(|p| {
+            let idx = mb.point2d_to_index(p);
+            mb.map_data[idx] = TileType::Floor;
+        });
+        self.rooms.push(room);
+        room
+    }
+}
+
+fn connect_rooms(lhs: &Rect, rhs: &Rect, mb: &mut MapBuilder, rng: &mut GameRng) {
+    let lhs = lhs.center();
+    let rhs = rhs.center();
+    if rng.gen_range(0..2) == 1 {
+        mb.apply_horizontal_tunnel(lhs.x, rhs.x, lhs.y);
+        mb.apply_vertical_tunnel(lhs.y, rhs.y, rhs.x);
+    } else {
+        mb.apply_vertical_tunnel(lhs.y, rhs.y, lhs.x);
+        mb.apply_horizontal_tunnel(lhs.x, rhs.x, rhs.y);
+    }
+}
+
+impl MapArchitect for BspArchitect {
+    fn architect(&mut self, rng: &mut GameRng) -> MapBuilder {
+        let mut mb = MapBuilder::default();
+
+        let map = Rect::with_size(0, 0, MAP_WIDTH as i32, MAP_HEIGHT as i32);
+        self.partition(map, &mut mb, rng);
+        mb.place_doors(&self.rooms);
+
+        mb
+    }
+}
@@ -0,0 +1,83 @@
+use super::*;
+use crate::prelude::*;
+
+impl MapBuilder {
+    // Returns the fraction of floor which was connected to the largest region beforehand
+    pub(super) fn ensure_connectivity(&mut self, strategy: ConnectivityStrategy) -> f32 {
+        let mut regions = self.find_regions();
+        regions.sort_by_key(|region| std::cmp::Reverse(region.len()));
+
+        let floor_count: usize = regions.iter().map(Vec::len).sum();
+        let mut regions = regions.into_iter();
+        let main_region = match regions.next() {
+            Some(main_region) => main_region,
+            None => return 1.0,
+        };
+        let connectivity = main_region.len() as f32 / floor_count as f32;
+
+        for pocket in regions {
+            match strategy {
+                ConnectivityStrategy::FillPockets => {
+                    for idx in pocket {
+                        self.map_data[idx] = TileType::Wall;
+                    }
+                }
+                ConnectivityStrategy::TunnelPockets => {
+                    // Dig from the pocket to the closest tile of the main region
+                    let (from, to) = pocket
+                        .iter()
+                        .flat_map(|&from| main_region.iter().map(move |&to| (from, to)))
+                        .map(|(from, to)| (self.index_to_point2d(from), self.index_to_point2d(to)))
+                        .min_by_key(|&(from, to)| {
+                            DistanceAlg::Manhattan.distance2d(from, to) as i32
+                        })
+                        .unwrap();
+                    self.apply_horizontal_tunnel(from.x, to.x, from.y);
+                    self.apply_vertical_tunnel(from.y, to.y, to.x);
+                }
+            }
+        }
+
+        connectivity
+    }
+
+    fn find_regions(&self) -> Vec<Vec<usize>> {
+        let mut visited = vec![false; self.map_data.len()];
+        (0..self.map_data.len())
+            .filter(|&start| self.map_data[start].movement_cost(false).is_some())
+            .filter_map(|start| {
+                if visited[start] {
+                    None
+                } else {
+                    Some(self.flood_fill(start, &mut visited))
+                }
+            })
+            .collect()
+    }
+
+    // Collects the tiles which can be walked to from `start` without passing through visited ones
+    pub(super) fn flood_fill(&self, start: usize, visited: &mut [bool]) -> Vec<usize> {
+        let mut region = Vec::new();
+        let mut stack = vec![start];
+        visited[start] = true;
+        while let Some(idx) = stack.pop() {
+            region.push(idx);
+            let p = self.index_to_point2d(idx);
+            for delta in [
+                Point::new(-1, 0),
+                Point::new(1, 0),
+                Point::new(0, -1),
+                Point::new(0, 1),
+            ] {
+                if let Some(neighbor) = self.try_idx(p + delta) {
+                    if !visited[neighbor] && self.map_data[neighbor].movement_cost(false).is_some()
+                    {
+                        visited[neighbor] = true;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+        region
+    }
+}
@@ -0,0 +1,85 @@
+use super::*;
+use crate::prelude::*;
+use rand::Rng;
+
+const MAX_STAGGERS: usize = 1000;
+
+pub struct DrunkardsWalkArchitect {
+    floor_percentage: usize,
+    lifetime: usize,
+    spawn: DrunkardSpawn,
+}
+
+impl DrunkardsWalkArchitect {
+    pub fn new(floor_percentage: usize, lifetime: usize, spawn: DrunkardSpawn) -> Self {
+        DrunkardsWalkArchitect {
+            floor_percentage,
+            lifetime,
+            spawn,
+        }
+    }
+
+    fn spawn_point(&self, mb: &MapBuilder, rng: &mut GameRng) -> Point {
+        let center = mb.dimensions() / 2;
+        match self.spawn {
+            DrunkardSpawn::Center => center,
+            DrunkardSpawn::RandomFloor => {
+                let floor_tiles: Vec<_> = (0..mb.map_data.len())
+                    .filter(|&idx| mb.map_data[idx] == TileType::Floor)
+                    .collect();
+                if floor_tiles.is_empty() {
+                    center
+                } else {
+                    mb.index_to_point2d(floor_tiles[rng.gen_range(0..floor_tiles.len())])
+                }
+            }
+        }
+    }
+
+    fn stagger(&self, start: Point, mb: &mut MapBuilder, rng: &mut GameRng) {
+        let mut position = start;
+        for _ in 0..self.lifetime {
+            let idx = mb.point2d_to_index(position);
+            mb.map_data[idx] = TileType::Floor;
+
+            let delta = match rng.gen_range(0..4) {
+                0 => Point::new(-1, 0),
+                1 => Point::new(1, 0),
+                2 => Point::new(0, -1),
+                _ => Point::new(0, 1),
+            };
+            // Keep a solid wall around the edge of the map
+            let next = position + delta;
+            if (1..MAP_WIDTH as i32 - 1).contains(&next.x)
+                && (1..MAP_HEIGHT as i32 - 1).contains(&next.y)
+            {
+                position = next;
+            }
+        }
+    }
+}
+
+impl MapArchitect for DrunkardsWalkArchitect {
+    fn architect(&mut self, rng: &mut GameRng) -> MapBuilder {
+        let mut mb = MapBuilder::default();
+
+        let interior_tiles = (MAP_WIDTH - 2) * (MAP_HEIGHT - 2);
+        let target_floor = interior_tiles * self.floor_percentage.min(100) / 100;
+        // Short lived drunkards may give up before covering as much floor as asked for
+        for _ in 0..MAX_STAGGERS {
+            let floor_tiles = mb
+                .map_data
+                .iter()
+                .filter(|&&tile| tile == TileType::Floor)
+                .count();
+            if floor_tiles >= target_floor {
+                break;
+            }
+
+            let start = self.spawn_point(&mb, rng);
+            self.stagger(start, &mut mb, rng);
+        }
+
+        mb
+    }
+}
@@ -0,0 +1,58 @@
+use super::*;
+use crate::prelude::*;
+use rand::seq::SliceRandom;
+
+impl MapBuilder {
+    pub(super) fn place_locks(&mut self, key_ids: &[u32], rng: &mut GameRng) {
+        let player_idx = self.point2d_to_index(self.player_start.into());
+        let amulet_idx = self.point2d_to_index(self.amulet_start.into());
+        for &key_id in key_ids {
+            // Earlier locks already cut off parts of the map, so compare against what is reachable now
+            let mut unlocked_visited = vec![false; self.map_data.len()];
+            self.flood_fill(player_idx, &mut unlocked_visited);
+
+            let mut doors: Vec<_> = (0..self.map_data.len())
+                .filter(|&idx| self.map_data[idx] == TileType::DoorClosed)
+                .collect();
+            doors.shuffle(rng);
+
+            for door in doors {
+                self.map_data[door] = TileType::DoorLocked(key_id);
+
+                // Only lock doors which cut off part of the map, and keep the key on the player's side
+                let mut visited = vec![false; self.map_data.len()];
+                let reachable = self.flood_fill(player_idx, &mut visited);
+                let locked_away = unlocked_visited.iter().zip(&visited).enumerate().any(
+                    |(idx, (&reached_unlocked, &reached))| {
+                        idx != door && reached_unlocked && !reached
+                    },
+                );
+                let occupied: Vec<Position> = self
+                    .mob_spawns
+                    .iter()
+                    .chain(self.item_spawns.iter())
+                    .map(|(position, _)| *position)
+                    .chain(self.key_spawns.iter().map(|(position, _)| *position))
+                    .collect();
+                let key_locations: Vec<_> = reachable
+                    .into_iter()
+                    .filter(|&idx| {
+                        idx != player_idx
+                            && idx != amulet_idx
+                            && self.map_data[idx] == TileType::Floor
+                    })
+                    .map(|idx| Position::from(self.index_to_point2d(idx)))
+                    .filter(|position| !occupied.contains(position))
+                    .collect();
+
+                match key_locations.choose(rng) {
+                    Some(&key_location) if locked_away => {
+                        self.key_spawns.push((key_location, key_id));
+                        break;
+                    }
+                    _ => self.map_data[door] = TileType::DoorClosed,
+                }
+            }
+        }
+    }
+}
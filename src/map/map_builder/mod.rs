@@ -1,39 +1,225 @@
 mod automata;
+mod bsp;
+mod connectivity;
+mod drunkard;
+mod locks;
 mod rooms;
+mod terrain;
+mod vaults;
+mod wfc;
 
 use crate::prelude::*;
 
 trait MapArchitect {
-    fn architect(&mut self) -> MapBuilder;
+    fn architect(&mut self, rng: &mut GameRng) -> MapBuilder;
+}
+
+fn build_architect<'a>(
+    architect: &ArchitectData,
+    samples: &[&'a SampleMapData],
+) -> Box<dyn MapArchitect + 'a> {
+    match *architect {
+        ArchitectData::Rooms {
+            num_rooms,
+            min_room_size,
+            max_room_size,
+        } => Box::new(rooms::RoomsArchitect::new(
+            num_rooms,
+            min_room_size,
+            max_room_size,
+        )),
+        ArchitectData::Bsp {
+            min_leaf_size,
+            min_room_size,
+        } => Box::new(bsp::BspArchitect::new(min_leaf_size, min_room_size)),
+        ArchitectData::CellularAutomata {
+            fill_percentage,
+            iterations,
+        } => Box::new(automata::CellularAutomataArchitect::new(
+            fill_percentage,
+            iterations,
+        )),
+        ArchitectData::DrunkardsWalk {
+            floor_percentage,
+            lifetime,
+            spawn,
+        } => Box::new(drunkard::DrunkardsWalkArchitect::new(
+            floor_percentage,
+            lifetime,
+            spawn,
+        )),
+        ArchitectData::WaveFunctionCollapse {
+            ref sample,
+            pattern_size,
+            ref fallback,
+        } => {
+            let fallback = build_architect(fallback, samples);
+            match samples.iter().find(|data| &data.name == sample) {
+                Some(&sample) => Box::new(wfc::WaveFunctionCollapseArchitect::new(
+                    sample,
+                    pattern_size,
+                    fallback,
+                )),
+                None => {
+                    warn!("Unknown sample map {}", sample);
+                    fallback
+                }
+            }
+        }
+    }
 }
 
 pub struct MapBuilder {
     pub map_data: Vec<TileType>,
+    pub player_start: Position,
+    pub amulet_start: Position,
+    pub mob_spawns: Vec<(Position, String)>,
+    pub item_spawns: Vec<(Position, String)>,
+    pub key_spawns: Vec<(Position, u32)>,
+    pub connectivity: f32,
+    movement_rules: MovementRules,
+    vault_areas: Vec<bracket_geometry::prelude::Rect>,
 }
 
 impl MapBuilder {
-    pub fn new() -> Self {
-        // rooms::RoomsArchitect::default().architect()
-        automata::CellularAutomataArchitect::default().architect()
+    pub fn new(
+        biome: Option<&BiomeData>,
+        vaults: &[&VaultData],
+        samples: &[&SampleMapData],
+        rng: &mut GameRng,
+        movement_rules: MovementRules,
+    ) -> Self {
+        let architect = biome
+            .map(|biome| biome.architect.clone())
+            .unwrap_or_default();
+        let mut mb = build_architect(&architect, samples).architect(rng);
+        if !mb.map_data.contains(&TileType::Floor) {
+            warn!("Map without any floor, falling back to the default architect");
+            mb = build_architect(&ArchitectData::default(), samples).architect(rng);
+        }
+        mb.movement_rules = movement_rules;
+        let connectivity = biome.map(|biome| biome.connectivity).unwrap_or_default();
+        mb.connectivity = mb.ensure_connectivity(connectivity);
+        mb.find_start_positions();
+        if !vaults.is_empty() {
+            // Vault walls may cut off parts of the level, so connect it up again and pick start
+            // positions which can still reach each other
+            mb.stamp_vaults(vaults, rng);
+            mb.ensure_connectivity(connectivity);
+            mb.find_start_positions();
+            mb.drop_walled_in_spawns();
+        }
+        if let Some(biome) = biome {
+            mb.place_terrain(&biome.terrain, rng);
+            mb.place_locks(&biome.locks, rng);
+        }
+        mb
     }
 
-    fn can_enter_tile(&self, p: Point) -> bool {
-        self.in_bounds(p) && !self.is_opaque(self.point2d_to_index(p))
+    pub fn place_stairs(&mut self) {
+        let idx = self.point2d_to_index(self.amulet_start.into());
+        self.map_data[idx] = TileType::Stairs;
     }
 
-    fn try_idx(&self, p: Point) -> Option<usize> {
-        if self.in_bounds(p) {
-            Some(self.point2d_to_index(p))
-        } else {
-            None
+    fn find_start_positions(&mut self) {
+        let center = self.dimensions() / 2;
+        let vault_areas = self.vault_areas.clone();
+        let outside_vaults = |p: &Point| !vault_areas.iter().any(|area| area.point_in_rect(*p));
+
+        // Carve out a tile for the player should the architect have left no floor at all
+        let player_start = (0..self.map_data.len())
+            .filter(|&idx| self.map_data[idx] == TileType::Floor)
+            .map(|idx| self.index_to_point2d(idx))
+            .filter(outside_vaults)
+            .min_by_key(|&p| DistanceAlg::PythagorasSquared.distance2d(center, p) as i64)
+            .unwrap_or_else(|| {
+                let idx = self.point2d_to_index(center);
+                self.map_data[idx] = TileType::Floor;
+                center
+            });
+
+        let dijkstra_map = DijkstraMap::new(
+            MAP_WIDTH,
+            MAP_HEIGHT,
+            &[self.point2d_to_index(player_start)],
+            self,
+            1024.0,
+        );
+
+        const UNREACHABLE: &f32 = &f32::MAX;
+        let amulet_start = dijkstra_map
+            .map
+            .iter()
+            .enumerate()
+            .filter(|(_, dist)| *dist < UNREACHABLE)
+            .filter(|&(idx, _)| outside_vaults(&self.index_to_point2d(idx)))
+            .max_by(|(_, lhs_dist), (_, rhs_dist)| lhs_dist.partial_cmp(rhs_dist).unwrap())
+            .map_or(player_start, |(idx, _)| self.index_to_point2d(idx));
+
+        self.player_start = player_start.into();
+        self.amulet_start = amulet_start.into();
+    }
+
+    // Forgets vault spawns whose tiles were walled off by the connectivity pass
+    fn drop_walled_in_spawns(&mut self) {
+        let floor: Vec<_> = (0..self.map_data.len())
+            .filter(|&idx| self.map_data[idx] == TileType::Floor)
+            .map(|idx| Position::from(self.index_to_point2d(idx)))
+            .collect();
+        self.mob_spawns
+            .retain(|(position, _)| floor.contains(position));
+        self.item_spawns
+            .retain(|(position, _)| floor.contains(position));
+    }
+
+    fn path_cost(&self, p: Point) -> Option<f32> {
+        self.try_idx(p)
+            .and_then(|idx| self.map_data[idx].path_cost(false))
+    }
+
+    // Closes off corridors where they enter the given rooms
+    fn place_doors(&mut self, rooms: &[bracket_geometry::prelude::Rect]) {
+        for room in rooms {
+            let ring = (room.x1..room.x2)
+                .flat_map(|x| [Point::new(x, room.y1 - 1), Point::new(x, room.y2)])
+                .chain(
+                    (room.y1..room.y2)
+                        .flat_map(|y| [Point::new(room.x1 - 1, y), Point::new(room.x2, y)]),
+                );
+            for p in ring {
+                let is_wall = |delta: Point| {
+                    self.try_idx(p + delta)
+                        .map(|idx| self.map_data[idx] == TileType::Wall)
+                        .unwrap_or(true)
+                };
+                let is_junction = (is_wall(Point::new(-1, 0)) && is_wall(Point::new(1, 0)))
+                    || (is_wall(Point::new(0, -1)) && is_wall(Point::new(0, 1)));
+                let next_to_door = [
+                    Point::new(-1, 0),
+                    Point::new(1, 0),
+                    Point::new(0, -1),
+                    Point::new(0, 1),
+                ]
+                .into_iter()
+                .filter_map(|delta| self.try_idx(p + delta))
+                .any(|idx| self.map_data[idx] == TileType::DoorClosed);
+                match self.try_idx(p) {
+                    Some(idx)
+                        if self.map_data[idx] == TileType::Floor
+                            && is_junction
+                            && !next_to_door =>
+                    {
+                        self.map_data[idx] = TileType::DoorClosed;
+                    }
+                    _ => {}
+                }
+            }
         }
     }
 
-    fn valid_exit(&self, loc: Point, delta: Point) -> Option<usize> {
-        let destination = loc + delta;
-        if self.can_enter_tile(destination) {
-            let idx = self.point2d_to_index(destination);
-            Some(idx)
+    fn try_idx(&self, p: Point) -> Option<usize> {
+        if self.in_bounds(p) {
+            Some(self.point2d_to_index(p))
         } else {
             None
         }
@@ -62,27 +248,30 @@ impl Default for MapBuilder {
     fn default() -> Self {
         MapBuilder {
             map_data: vec![TileType::Wall; MAP_WIDTH * MAP_HEIGHT],
+            player_start: Position::default(),
+            amulet_start: Position::default(),
+            mob_spawns: Vec::new(),
+            item_spawns: Vec::new(),
+            key_spawns: Vec::new(),
+            connectivity: 1.0,
+            movement_rules: MovementRules::default(),
+            vault_areas: Vec::new(),
         }
     }
 }
 
 impl BaseMap for MapBuilder {
     fn is_opaque(&self, idx: usize) -> bool {
-        self.map_data[idx] != TileType::Floor
+        self.map_data[idx].is_opaque()
     }
 
     fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
         let location = self.index_to_point2d(idx);
-        [
-            Point::new(-1, 0),
-            Point::new(1, 0),
-            Point::new(0, -1),
-            Point::new(0, 1),
-        ]
-        .into_iter()
-        .filter_map(|delta| self.valid_exit(location, delta))
-        .map(|idx| (idx, 1.0))
-        .collect()
+        self.movement_rules
+            .exits(location, |p| self.path_cost(p))
+            .into_iter()
+            .map(|(p, cost)| (self.point2d_to_index(p), cost))
+            .collect()
     }
 
     fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
@@ -1,22 +1,38 @@
 use super::*;
 use crate::prelude::*;
-use rand::prelude::*;
+use rand::Rng;
 
-const NUM_ROOMS: usize = 20;
+const MAX_PLACEMENT_ATTEMPTS: usize = 1000;
 
-#[derive(Default)]
 pub struct RoomsArchitect {
+    num_rooms: usize,
+    min_room_size: i32,
+    max_room_size: i32,
     rooms: Vec<bracket_geometry::prelude::Rect>,
 }
 
 impl RoomsArchitect {
-    fn build_random_rooms(&mut self, mb: &mut MapBuilder, rng: &mut ThreadRng) {
-        while self.rooms.len() < NUM_ROOMS {
+    pub fn new(num_rooms: usize, min_room_size: i32, max_room_size: i32) -> Self {
+        RoomsArchitect {
+            num_rooms,
+            min_room_size,
+            max_room_size,
+            rooms: Vec::new(),
+        }
+    }
+
+    fn build_random_rooms(&mut self, mb: &mut MapBuilder, rng: &mut GameRng) {
+        // Crowded maps may end up with fewer rooms than asked for
+        for _ in 0..MAX_PLACEMENT_ATTEMPTS {
+            if self.rooms.len() >= self.num_rooms {
+                break;
+            }
+
             let room = bracket_geometry::prelude::Rect::with_size(
-                rng.gen_range(1..MAP_WIDTH - 10),
-                rng.gen_range(1..MAP_HEIGHT - 10),
-                rng.gen_range(2..10),
-                rng.gen_range(2..10),
+                rng.gen_range(1..MAP_WIDTH as i32 - self.max_room_size - 1),
+                rng.gen_range(1..MAP_HEIGHT as i32 - self.max_room_size - 1),
+                rng.gen_range(self.min_room_size..=self.max_room_size),
+                rng.gen_range(self.min_room_size..=self.max_room_size),
             );
 
             let overlap = self.rooms.iter().any(|r| r.intersect(&room));
@@ -31,9 +47,9 @@ impl RoomsArchitect {
         }
     }
 
-    fn build_corridors(&mut self, mb: &mut MapBuilder, rng: &mut ThreadRng) {
+    fn build_corridors(&mut self, mb: &mut MapBuilder, rng: &mut GameRng) {
         let mut rooms = self.rooms.clone();
-        rooms.sort_by(|a, b| a.center().x.cmp(&b.center().x));
+        rooms.sort_by_key(|room| room.center().x);
 
         use itertools::Itertools;
         for (prev_room, new_room) in rooms.iter().tuple_windows() {
@@ -51,12 +67,12 @@ impl RoomsArchitect {
 }
 
 impl MapArchitect for RoomsArchitect {
-    fn architect(&mut self) -> MapBuilder {
+    fn architect(&mut self, rng: &mut GameRng) -> MapBuilder {
         let mut mb = MapBuilder::default();
 
-        let mut rng = thread_rng();
-        self.build_random_rooms(&mut mb, &mut rng);
-        self.build_corridors(&mut mb, &mut rng);
+        self.build_random_rooms(&mut mb, rng);
+        self.build_corridors(&mut mb, rng);
+        mb.place_doors(&self.rooms);
 
         mb
     }
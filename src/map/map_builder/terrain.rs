@@ -0,0 +1,93 @@
+use super::*;
+use crate::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+impl MapBuilder {
+    pub(super) fn place_terrain(&mut self, terrain: &[TerrainData], rng: &mut GameRng) {
+        let reserved: Vec<usize> = [self.player_start, self.amulet_start]
+            .into_iter()
+            .chain(self.mob_spawns.iter().map(|(position, _)| *position))
+            .chain(self.item_spawns.iter().map(|(position, _)| *position))
+            .map(|position| self.point2d_to_index(position.into()))
+            .collect();
+
+        for data in terrain {
+            for _ in 0..data.pools {
+                let floor: Vec<_> = (0..self.map_data.len())
+                    .filter(|&idx| self.map_data[idx] == TileType::Floor)
+                    .filter(|idx| !reserved.contains(idx))
+                    .collect();
+                let start = match floor.choose(rng) {
+                    Some(&start) => start,
+                    None => return,
+                };
+
+                let pool = self.grow_pool(start, data.pool_size, &reserved, rng);
+                for &idx in &pool {
+                    self.map_data[idx] = data.tile;
+                }
+
+                // Pools which can't be walked through must not cut off any part of the map
+                if data.tile.movement_cost(false).is_none() && !self.is_fully_connected() {
+                    for &idx in &pool {
+                        self.map_data[idx] = TileType::Floor;
+                    }
+                }
+            }
+        }
+    }
+
+    // Randomly spreads out from the start tile over neighbouring floor
+    fn grow_pool(
+        &self,
+        start: usize,
+        size: usize,
+        reserved: &[usize],
+        rng: &mut GameRng,
+    ) -> Vec<usize> {
+        let mut pool = vec![start];
+        let mut frontier = vec![start];
+        while pool.len() < size && !frontier.is_empty() {
+            let i = rng.gen_range(0..frontier.len());
+            let p = self.index_to_point2d(frontier[i]);
+            let neighbors: Vec<_> = [
+                Point::new(-1, 0),
+                Point::new(1, 0),
+                Point::new(0, -1),
+                Point::new(0, 1),
+            ]
+            .into_iter()
+            .filter_map(|delta| self.try_idx(p + delta))
+            .filter(|idx| {
+                self.map_data[*idx] == TileType::Floor
+                    && !pool.contains(idx)
+                    && !reserved.contains(idx)
+            })
+            .collect();
+
+            match neighbors.choose(rng) {
+                Some(&next) => {
+                    pool.push(next);
+                    frontier.push(next);
+                }
+                None => {
+                    frontier.swap_remove(i);
+                }
+            }
+        }
+        pool
+    }
+
+    fn is_fully_connected(&self) -> bool {
+        let mut visited = vec![false; self.map_data.len()];
+        self.flood_fill(
+            self.point2d_to_index(self.player_start.into()),
+            &mut visited,
+        );
+        visited
+            .iter()
+            .zip(&self.map_data)
+            .all(|(&visited, tile)| visited || tile.movement_cost(false).is_none())
+    }
+}
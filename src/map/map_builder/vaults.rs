@@ -0,0 +1,65 @@
+use super::*;
+use crate::prelude::*;
+use bracket_geometry::prelude::Rect;
+use rand::seq::SliceRandom;
+
+impl MapBuilder {
+    pub(super) fn stamp_vaults(&mut self, vaults: &[&VaultData], rng: &mut GameRng) {
+        let mut stamped: Vec<Rect> = Vec::new();
+        for vault in vaults {
+            let width = vault.grid.iter().map(|row| row.chars().count()).max();
+            let height = vault.grid.len();
+            let (width, height) = match width {
+                Some(width) if width > 0 && height > 0 => (width as i32, height as i32),
+                _ => continue,
+            };
+
+            // A vault fits wherever it covers nothing but open floor away from the start positions
+            let candidates: Vec<_> = (1..MAP_HEIGHT as i32 - height)
+                .flat_map(|y| (1..MAP_WIDTH as i32 - width).map(move |x| (x, y)))
+                .map(|(x, y)| Rect::with_size(x, y, width, height))
+                .filter(|area| !stamped.iter().any(|other| other.intersect(area)))
+                .filter(|area| {
+                    !area.point_in_rect(self.player_start.into())
+                        && !area.point_in_rect(self.amulet_start.into())
+                })
+                .filter(|area| {
+                    let mut is_empty = true;
+                    area.for_each(|p| {
+                        is_empty &= self.map_data[self.point2d_to_index(p)] == TileType::Floor
+                    });
+                    is_empty
+                })
+                .collect();
+
+            if let Some(&area) = candidates.choose(rng) {
+                self.stamp_vault(vault, area);
+                stamped.push(area);
+            }
+        }
+        self.vault_areas = stamped;
+    }
+
+    fn stamp_vault(&mut self, vault: &VaultData, area: Rect) {
+        for (row_idx, row) in vault.grid.iter().enumerate() {
+            // The first row of the grid is the northernmost one
+            let y = area.y2 - 1 - row_idx as i32;
+            for (col_idx, c) in row.chars().enumerate() {
+                let position = Position::new(area.x1 + col_idx as i32, y);
+                let idx = self.point2d_to_index(position.into());
+                match vault.legend.get(&c) {
+                    Some(VaultCell::Tile(tile_type)) => self.map_data[idx] = *tile_type,
+                    Some(VaultCell::Mob(name)) => {
+                        self.map_data[idx] = TileType::Floor;
+                        self.mob_spawns.push((position, name.clone()));
+                    }
+                    Some(VaultCell::Item(name)) => {
+                        self.map_data[idx] = TileType::Floor;
+                        self.item_spawns.push((position, name.clone()));
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+}
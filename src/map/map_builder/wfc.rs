@@ -0,0 +1,323 @@
+use super::*;
+use crate::prelude::*;
+use rand::Rng;
+
+const MAX_ATTEMPTS: usize = 5;
+
+// Offsets of the neighbouring cells, indexed such that `DIRECTIONS[d ^ 1]` is opposite to `DIRECTIONS[d]`
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+struct Pattern {
+    tiles: Vec<TileType>,
+    frequency: usize,
+}
+
+pub struct WaveFunctionCollapseArchitect<'a> {
+    sample: &'a SampleMapData,
+    pattern_size: usize,
+    fallback: Box<dyn MapArchitect + 'a>,
+}
+
+impl<'a> WaveFunctionCollapseArchitect<'a> {
+    pub fn new(
+        sample: &'a SampleMapData,
+        pattern_size: usize,
+        fallback: Box<dyn MapArchitect + 'a>,
+    ) -> Self {
+        WaveFunctionCollapseArchitect {
+            sample,
+            pattern_size: pattern_size.max(1),
+            fallback,
+        }
+    }
+
+    fn sample_tiles(&self) -> Vec<Vec<TileType>> {
+        let width = self
+            .sample
+            .grid
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or_default();
+        self.sample
+            .grid
+            .iter()
+            .map(|row| {
+                let mut tiles: Vec<_> = row
+                    .chars()
+                    .map(|c| {
+                        self.sample
+                            .legend
+                            .get(&c)
+                            .cloned()
+                            .unwrap_or(TileType::Wall)
+                    })
+                    .collect();
+                tiles.resize(width, TileType::Wall);
+                tiles
+            })
+            .collect()
+    }
+
+    fn extract_patterns(&self) -> Vec<Pattern> {
+        let sample = self.sample_tiles();
+        let height = sample.len();
+        let width = sample.first().map(Vec::len).unwrap_or_default();
+        let n = self.pattern_size;
+
+        // Patterns are kept in order of first appearance so that generation is reproducible
+        let mut patterns: Vec<Pattern> = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let tiles: Vec<_> = (0..n * n)
+                    .map(|i| sample[(y + i / n) % height][(x + i % n) % width])
+                    .collect();
+                match patterns.iter_mut().find(|pattern| pattern.tiles == tiles) {
+                    Some(pattern) => pattern.frequency += 1,
+                    None => patterns.push(Pattern {
+                        tiles,
+                        frequency: 1,
+                    }),
+                }
+            }
+        }
+        patterns
+    }
+
+    // Whether `rhs` may be placed at offset (dx, dy) from `lhs`, i.e. whether they agree where they overlap
+    fn agrees(&self, lhs: &Pattern, rhs: &Pattern, (dx, dy): (i32, i32)) -> bool {
+        let n = self.pattern_size as i32;
+        (0..n).all(|y| {
+            (0..n).all(|x| {
+                let (rx, ry) = (x - dx, y - dy);
+                !(0..n).contains(&rx)
+                    || !(0..n).contains(&ry)
+                    || lhs.tiles[(y * n + x) as usize] == rhs.tiles[(ry * n + rx) as usize]
+            })
+        })
+    }
+
+    fn generate(
+        &self,
+        patterns: &[Pattern],
+        propagator: &[Vec<Vec<usize>>],
+        support: &mut Vec<[u16; 4]>,
+        rng: &mut GameRng,
+    ) -> Option<MapBuilder> {
+        let n = self.pattern_size;
+        let cells_wide = MAP_WIDTH - n + 1;
+        let cells_high = MAP_HEIGHT - n + 1;
+        let cell_count = cells_wide * cells_high;
+        let pattern_count = patterns.len();
+
+        let mut wave = vec![true; cell_count * pattern_count];
+        let mut remaining = vec![pattern_count; cell_count];
+        // Number of patterns in the neighbouring cell in each direction which still permit a pattern,
+        // refilled in place so that retries don't allocate the whole grid again
+        support.clear();
+        support.extend((0..cell_count).flat_map(|_| {
+            (0..pattern_count).map(|p| {
+                let mut support = [0; 4];
+                for (d, count) in support.iter_mut().enumerate() {
+                    *count = propagator[d ^ 1][p].len() as u16;
+                }
+                support
+            })
+        }));
+        let mut banned: Vec<(usize, usize)> = Vec::new();
+
+        let neighbor = |cell: usize, d: usize| -> Option<usize> {
+            let (dx, dy) = DIRECTIONS[d];
+            let x = (cell % cells_wide) as i32 + dx;
+            let y = (cell / cells_wide) as i32 + dy;
+            if (0..cells_wide as i32).contains(&x) && (0..cells_high as i32).contains(&y) {
+                Some(y as usize * cells_wide + x as usize)
+            } else {
+                None
+            }
+        };
+
+        loop {
+            // Observe the undecided cell with the fewest remaining options, breaking ties randomly
+            let cell = (0..cell_count)
+                .filter(|&cell| remaining[cell] > 1)
+                .map(|cell| (remaining[cell] as f32 + rng.gen::<f32>() * 0.5, cell))
+                .min_by(|(lhs, _), (rhs, _)| lhs.partial_cmp(rhs).unwrap())
+                .map(|(_, cell)| cell);
+            let cell = match cell {
+                Some(cell) => cell,
+                None => break,
+            };
+
+            let options = cell * pattern_count..(cell + 1) * pattern_count;
+            let total: usize = options
+                .clone()
+                .filter(|&i| wave[i])
+                .map(|i| patterns[i % pattern_count].frequency)
+                .sum();
+            let mut choice = rng.gen_range(0..total);
+            let chosen = options
+                .clone()
+                .filter(|&i| wave[i])
+                .map(|i| i % pattern_count)
+                .find(|&p| {
+                    if choice < patterns[p].frequency {
+                        true
+                    } else {
+                        choice -= patterns[p].frequency;
+                        false
+                    }
+                })
+                .unwrap();
+            for p in (0..pattern_count).filter(|&p| p != chosen) {
+                if wave[cell * pattern_count + p] {
+                    wave[cell * pattern_count + p] = false;
+                    remaining[cell] -= 1;
+                    banned.push((cell, p));
+                }
+            }
+
+            // Propagate the consequences to the neighbouring cells
+            while let Some((cell, p)) = banned.pop() {
+                for d in 0..DIRECTIONS.len() {
+                    let other = match neighbor(cell, d) {
+                        Some(other) => other,
+                        None => continue,
+                    };
+                    for &q in &propagator[d][p] {
+                        let count = &mut support[other * pattern_count + q][d];
+                        *count -= 1;
+                        if *count == 0 && wave[other * pattern_count + q] {
+                            wave[other * pattern_count + q] = false;
+                            remaining[other] -= 1;
+                            if remaining[other] == 0 {
+                                return None;
+                            }
+                            banned.push((other, q));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Each tile is taken from the pattern of the nearest cell covering it
+        let mut mb = MapBuilder::default();
+        for y in 0..MAP_HEIGHT {
+            for x in 0..MAP_WIDTH {
+                let (cx, cy) = (x.min(cells_wide - 1), y.min(cells_high - 1));
+                let cell = cy * cells_wide + cx;
+                let p = (0..pattern_count).find(|&p| wave[cell * pattern_count + p])?;
+                let tile = patterns[p].tiles[(y - cy) * n + (x - cx)];
+
+                // Rows of the sample run from north to south
+                let idx = mb.point2d_to_index(Point::new(x, MAP_HEIGHT - 1 - y));
+                let on_edge = x == 0 || y == 0 || x == MAP_WIDTH - 1 || y == MAP_HEIGHT - 1;
+                mb.map_data[idx] = if on_edge { TileType::Wall } else { tile };
+            }
+        }
+        Some(mb)
+    }
+}
+
+impl MapArchitect for WaveFunctionCollapseArchitect<'_> {
+    fn architect(&mut self, rng: &mut GameRng) -> MapBuilder {
+        let patterns = self.extract_patterns();
+        let propagator: Vec<Vec<Vec<usize>>> = DIRECTIONS
+            .iter()
+            .map(|&delta| {
+                patterns
+                    .iter()
+                    .map(|lhs| {
+                        (0..patterns.len())
+                            .filter(|&q| self.agrees(lhs, &patterns[q], delta))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Support counts are stored as u16, which bounds the number of distinct patterns
+        if !patterns.is_empty()
+            && patterns.len() <= u16::MAX as usize
+            && self.pattern_size <= MAP_WIDTH.min(MAP_HEIGHT)
+        {
+            let mut support = Vec::new();
+            for _ in 0..MAX_ATTEMPTS {
+                if let Some(mb) = self.generate(&patterns, &propagator, &mut support, rng) {
+                    return mb;
+                }
+            }
+        }
+
+        warn!(
+            "Wave function collapse failed for sample map {}, falling back",
+            self.sample.name
+        );
+        self.fallback.architect(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::map_builder::automata::CellularAutomataArchitect;
+
+    // Vertical stripes of wall and floor, one tile wide
+    fn striped_sample() -> SampleMapData {
+        SampleMapData {
+            name: "Stripes".to_string(),
+            grid: vec!["#.".to_string(), "#.".to_string()],
+            legend: [('#', TileType::Wall), ('.', TileType::Floor)]
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn architect(sample: &SampleMapData) -> WaveFunctionCollapseArchitect<'_> {
+        let fallback = Box::new(CellularAutomataArchitect::new(45, 5));
+        WaveFunctionCollapseArchitect::new(sample, 2, fallback)
+    }
+
+    #[test]
+    fn patterns_wrap_around_the_sample() {
+        let sample = striped_sample();
+        let patterns = architect(&sample).extract_patterns();
+        assert_eq!(patterns.len(), 2);
+        assert!(patterns.iter().all(|pattern| pattern.frequency == 2));
+        assert_eq!(
+            patterns[0].tiles,
+            [
+                TileType::Wall,
+                TileType::Floor,
+                TileType::Wall,
+                TileType::Floor
+            ]
+        );
+    }
+
+    #[test]
+    fn patterns_agree_where_they_overlap() {
+        let sample = striped_sample();
+        let architect = architect(&sample);
+        let patterns = architect.extract_patterns();
+        let (wall_first, floor_first) = (&patterns[0], &patterns[1]);
+
+        assert!(architect.agrees(wall_first, floor_first, (1, 0)));
+        assert!(architect.agrees(wall_first, floor_first, (-1, 0)));
+        assert!(!architect.agrees(wall_first, wall_first, (1, 0)));
+        assert!(architect.agrees(wall_first, wall_first, (0, 1)));
+        assert!(!architect.agrees(wall_first, floor_first, (0, 1)));
+    }
+
+    #[test]
+    fn generated_map_keeps_the_stripes() {
+        let sample = striped_sample();
+        let mb = architect(&sample).architect(&mut GameRng::new(Some(1)));
+        for y in 1..MAP_HEIGHT as i32 - 1 {
+            for x in 1..MAP_WIDTH as i32 - 2 {
+                let tile = |x| mb.map_data[mb.point2d_to_index(Point::new(x, y))];
+                assert_ne!(tile(x), tile(x + 1));
+            }
+        }
+    }
+}
@@ -1,12 +1,17 @@
+mod doors;
 mod map_builder;
 mod mouse;
+mod movement_rules;
 mod query_adapter;
 
 use crate::prelude::*;
-use rand::{seq::SliceRandom, thread_rng};
-use serde::Deserialize;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 
+pub use doors::{WantsToCloseDoor, WantsToOpenDoor};
 use map_builder::*;
+pub use mouse::TileInteraction;
+pub use movement_rules::{MovementRules, NewGameMovementRules};
 pub use query_adapter::*;
 
 pub const MAP_SIZE: MapSize = MapSize(10, 6);
@@ -33,33 +38,102 @@ pub fn tile_center(Position(vec): &Position) -> Vec3 {
     )
 }
 
-#[derive(Copy, Clone, Component, Debug, Hash, Eq, PartialEq, Deserialize)]
+// Extra cost for pathing across hazardous tiles, so that they are only crossed as a last resort
+const HAZARD_PATH_COST: f32 = 20.0;
+
+#[derive(Copy, Clone, Component, Debug, Hash, Eq, PartialEq, Deserialize, Serialize)]
 pub enum TileType {
     Wall,
     Floor,
+    Stairs,
+    DoorClosed,
+    DoorOpen,
+    DoorLocked(u32),
+    ShallowWater,
+    DeepWater,
+    Lava,
 }
 
-struct CurrentBiome(Option<BiomeData>);
+impl TileType {
+    // Time taken to step onto the tile relative to plain floor, if it can be entered at all;
+    // closed doors take a turn to open
+    pub fn movement_cost(&self, can_swim: bool) -> Option<f32> {
+        match self {
+            TileType::Wall | TileType::DoorLocked(_) => None,
+            TileType::DeepWater if !can_swim => None,
+            TileType::ShallowWater if !can_swim => Some(2.0),
+            TileType::DoorClosed => Some(2.0),
+            TileType::Floor
+            | TileType::Stairs
+            | TileType::DoorOpen
+            | TileType::ShallowWater
+            | TileType::DeepWater
+            | TileType::Lava => Some(1.0),
+        }
+    }
+
+    pub fn path_cost(&self, can_swim: bool) -> Option<f32> {
+        let hazard_cost = if self.is_hazardous() {
+            HAZARD_PATH_COST
+        } else {
+            0.0
+        };
+        self.movement_cost(can_swim).map(|cost| cost + hazard_cost)
+    }
+
+    pub fn is_hazardous(&self) -> bool {
+        matches!(self, TileType::Lava)
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        matches!(
+            self,
+            TileType::Wall | TileType::DoorClosed | TileType::DoorLocked(_)
+        )
+    }
+}
+
+pub struct CurrentBiome(pub Option<BiomeData>);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CurrentLevel(pub usize);
+
+#[derive(Clone, Debug)]
+pub struct StartPositions {
+    pub player: Position,
+    pub amulet: Option<Position>,
+    pub mobs: Vec<(Position, String)>,
+    pub items: Vec<(Position, String)>,
+    pub keys: Vec<(Position, u32)>,
+}
 
 pub struct MapPlugin;
 
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(TilemapPlugin)
+            .add_plugin(doors::DoorPlugin)
             .insert_resource(ClearColor(Color::BLACK))
             .insert_resource(CurrentBiome(None))
-            .add_enter_system(TurnState::NewGame, spawn_map_layer)
+            .insert_resource(CurrentLevel::default())
+            .insert_resource(MovementRules::default())
+            .insert_resource(NewGameMovementRules(MovementRules::from_args()))
+            .add_enter_system(
+                TurnState::NewGame,
+                movement_rules::apply_new_game_movement_rules.before("spawn_map_layer"),
+            )
+            .add_enter_system(
+                TurnState::NewGame,
+                reset_current_level.before("spawn_map_layer"),
+            )
+            .add_enter_system(TurnState::NewGame, spawn_map_layer.label("spawn_map_layer"))
+            .add_enter_system(TurnState::NextLevel, spawn_map_layer)
             .add_event::<mouse::TileInteraction>()
             .add_system(
                 mouse::mouse_click_tile_interaction
                     .run_in_state(TurnState::AwaitingInput)
                     .label("mouse_click_tile_interaction"),
             )
-            .add_system(
-                mouse::hide_tiles_by_click
-                    .run_in_state(TurnState::AwaitingInput)
-                    .after("mouse_click_tile_interaction"),
-            )
             .add_system_to_stage(CoreStage::PostUpdate, sync_tiles);
     }
 }
@@ -81,21 +155,87 @@ impl TileBundleTrait for BevycraftTileBundle {
     }
 }
 
+fn reset_current_level(mut current_level: ResMut<CurrentLevel>) {
+    *current_level = CurrentLevel::default();
+}
+
 fn spawn_map_layer(
     mut commands: Commands,
     mut map_query: MapQuery,
     asset_server: Res<AssetServer>,
     biomes: Res<Assets<BiomeData>>,
+    vaults: Res<Assets<VaultData>>,
+    samples: Res<Assets<SampleMapData>>,
+    current_level: Res<CurrentLevel>,
+    movement_rules: Res<MovementRules>,
+    rng: Res<GameRng>,
 ) {
-    // Despawn the map in case of game reset
+    // Despawn the map in case of game reset or level change
     map_query.despawn(&mut commands, MAP_ID);
 
-    // Pick a random biome for now
-    let mut rng = thread_rng();
-    let biome_data: Vec<_> = biomes.iter().map(|(_, data)| data).collect();
+    // Pick a random biome among those available at the current depth
+    let CurrentLevel(level) = *current_level;
+    let mut rng = rng.level_rng(level);
+    let mut biome_data: Vec<_> = biomes
+        .iter()
+        .map(|(_, data)| data)
+        .filter(|data| data.levels.contains(&level))
+        .collect();
+    biome_data.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
     let biome = biome_data.choose(&mut rng).cloned();
+    let final_level = biomes
+        .iter()
+        .flat_map(|(_, data)| data.levels.iter().cloned())
+        .max()
+        .unwrap_or_default();
     commands.insert_resource(CurrentBiome(biome.cloned()));
 
+    let vaults: Vec<_> = biome
+        .iter()
+        .flat_map(|biome| biome.vaults.iter())
+        .filter_map(|name| vaults.iter().find(|(_, vault)| &vault.name == name))
+        .map(|(_, vault)| vault)
+        .collect();
+    let samples: Vec<_> = samples.iter().map(|(_, sample)| sample).collect();
+    let mut map_builder = MapBuilder::new(biome, &vaults, &samples, &mut rng, *movement_rules);
+    info!(
+        "Generated level {} with {:.1}% of its floor initially connected",
+        level,
+        100.0 * map_builder.connectivity
+    );
+    let amulet_start = if level < final_level {
+        map_builder.place_stairs();
+        None
+    } else {
+        Some(map_builder.amulet_start)
+    };
+    commands.insert_resource(StartPositions {
+        player: map_builder.player_start,
+        amulet: amulet_start,
+        mobs: map_builder.mob_spawns.clone(),
+        items: map_builder.item_spawns.clone(),
+        keys: map_builder.key_spawns.clone(),
+    });
+
+    build_map_layer(
+        &mut commands,
+        &mut map_query,
+        asset_server.as_ref(),
+        biome,
+        map_builder
+            .map_data
+            .iter()
+            .map(|&tile_type| (tile_type, false)),
+    );
+}
+
+pub fn build_map_layer(
+    commands: &mut Commands,
+    map_query: &mut MapQuery,
+    asset_server: &AssetServer,
+    biome: Option<&BiomeData>,
+    tiles: impl IntoIterator<Item = (TileType, bool)>,
+) {
     let texture_handle = asset_server.load("dungeonfont.png");
 
     // Create map entity and component:
@@ -104,41 +244,42 @@ fn spawn_map_layer(
 
     // Creates a new layer builder with a layer entity.
     let (mut layer_builder, _) = LayerBuilder::new(
-        &mut commands,
+        commands,
         LayerSettings::new(MAP_SIZE, CHUNK_SIZE, TILE_SIZE, TEXTURE_SIZE),
         MAP_ID,
         MAP_LAYER_ID,
     );
 
-    let map_builder = MapBuilder::new();
-    let mut map_iter = map_builder.map_data.iter();
+    let mut map_iter = tiles.into_iter();
 
     layer_builder.for_each_tiles_mut(|_, bundle| {
-        *bundle = map_iter.next().map(|&tile_type| BevycraftTileBundle {
-            tile_type,
-            tilemap_bundle: TileBundle {
-                tile: Tile {
-                    texture_index: biome
-                        .and_then(|biome| biome.tile_textures.get(&tile_type).cloned())
-                        .unwrap_or(176),
-                    visible: false,
+        *bundle = map_iter
+            .next()
+            .map(|(tile_type, visible)| BevycraftTileBundle {
+                tile_type,
+                tilemap_bundle: TileBundle {
+                    tile: Tile {
+                        texture_index: biome
+                            .and_then(|biome| biome.tile_texture(tile_type))
+                            .unwrap_or(176),
+                        visible,
+                        ..default()
+                    },
                     ..default()
                 },
-                ..default()
-            },
-        });
+            });
     });
     assert!(
         map_iter.next().is_none(),
-        "The map builder was not exhaustively consumed by the map."
+        "The tiles were not exhaustively consumed by the map."
     );
 
     // Builds the layer.
     // Note: Once this is called you can no longer edit the layer until a hard sync in bevy.
-    let layer_entity = map_query.build_layer(&mut commands, layer_builder, texture_handle);
+    let layer_entity = map_query.build_layer(commands, layer_builder, texture_handle);
 
     // Required to keep track of layers for a map internally.
-    map.add_layer(&mut commands, MAP_LAYER_ID, layer_entity);
+    map.add_layer(commands, MAP_LAYER_ID, layer_entity);
 
     // Spawn Map
     // Required in order to use map_query to retrieve layers/tiles.
@@ -160,7 +301,7 @@ fn sync_tiles(
 ) {
     if let CurrentBiome(Some(biome)) = current_biome.as_ref() {
         for (tile_type, tile_pos, mut tile) in tile_query.iter_mut() {
-            tile.texture_index = biome.tile_textures.get(tile_type).cloned().unwrap_or(176);
+            tile.texture_index = biome.tile_texture(*tile_type).unwrap_or(176);
             map_query.notify_chunk_for_tile(*tile_pos, MAP_ID, MAP_LAYER_ID);
         }
     }
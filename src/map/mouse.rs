@@ -1,7 +1,7 @@
 use crate::prelude::*;
 use bevy::input::mouse::MouseButtonInput;
 
-pub struct TileInteraction(TilePos);
+pub struct TileInteraction(pub TilePos);
 
 pub fn mouse_click_tile_interaction(
     mut mousebtn_evr: EventReader<MouseButtonInput>,
@@ -14,40 +14,26 @@ pub fn mouse_click_tile_interaction(
     let (camera, camera_transform) = q_camera.iter().next().unwrap();
 
     for ev in mousebtn_evr.iter() {
-        match ev.state {
-            ElementState::Pressed => {
-                let window = windows.get_primary().unwrap();
-                if let Some(screen_pos) = window.cursor_position() {
-                    // get the size of the window
-                    let window_size = Vec2::new(window.width() as f32, window.height() as f32);
-
-                    // convert screen position [0..resolution] to ndc [-1..1] (gpu coordinates)
-                    let ndc = (screen_pos / window_size) * 2.0 - Vec2::ONE;
-
-                    // matrix for undoing the projection and camera transform
-                    let ndc_to_world =
-                        camera_transform.compute_matrix() * camera.projection_matrix.inverse();
-
-                    // use it to convert ndc to world-space coordinates
-                    let world_pos = ndc_to_world.project_point3(ndc.extend(-1.0));
-
-                    // reduce it to a 2D value
-                    let world_pos: Vec2 = world_pos.truncate();
-                    tile_evw.send(TileInteraction(super::world_to_tile(&world_pos)));
-                }
-            }
-            _ => {}
-        }
-    }
-}
+        if ev.state == ElementState::Pressed {
+            let window = windows.get_primary().unwrap();
+            if let Some(screen_pos) = window.cursor_position() {
+                // get the size of the window
+                let window_size = Vec2::new(window.width(), window.height());
 
-pub fn hide_tiles_by_click(
-    mut tile_evr: EventReader<TileInteraction>,
-    mut tile_query: Query<(&TilePos, &mut TileType)>,
-) {
-    for TileInteraction(tile_pos) in tile_evr.iter() {
-        if let Some((_, mut tile_type)) = tile_query.iter_mut().find(|(pos, _)| *pos == tile_pos) {
-            *tile_type = TileType::Floor;
+                // convert screen position [0..resolution] to ndc [-1..1] (gpu coordinates)
+                let ndc = (screen_pos / window_size) * 2.0 - Vec2::ONE;
+
+                // matrix for undoing the projection and camera transform
+                let ndc_to_world =
+                    camera_transform.compute_matrix() * camera.projection_matrix.inverse();
+
+                // use it to convert ndc to world-space coordinates
+                let world_pos = ndc_to_world.project_point3(ndc.extend(-1.0));
+
+                // reduce it to a 2D value
+                let world_pos: Vec2 = world_pos.truncate();
+                tile_evw.send(TileInteraction(super::world_to_tile(&world_pos)));
+            }
         }
     }
 }
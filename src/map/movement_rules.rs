@@ -0,0 +1,147 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const ORTHOGONAL_DELTAS: [Point; 4] = [
+    Point::constant(-1, 0),
+    Point::constant(1, 0),
+    Point::constant(0, -1),
+    Point::constant(0, 1),
+];
+const DIAGONAL_DELTAS: [Point; 4] = [
+    Point::constant(-1, -1),
+    Point::constant(1, -1),
+    Point::constant(-1, 1),
+    Point::constant(1, 1),
+];
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct MovementRules {
+    pub diagonal: bool,
+}
+
+// Rules picked in the menu for the next new game, while a running game keeps its own
+#[derive(Clone, Copy, Debug)]
+pub struct NewGameMovementRules(pub MovementRules);
+
+impl MovementRules {
+    // Only the default for new games, as they can also be changed from the menu
+    pub fn from_args() -> Self {
+        MovementRules {
+            diagonal: std::env::args().any(|arg| arg == "--diagonal"),
+        }
+    }
+
+    pub fn allows_step(
+        &self,
+        from: Point,
+        to: Point,
+        mut can_enter: impl FnMut(Point) -> bool,
+    ) -> bool {
+        let delta = to - from;
+        match (delta.x.abs(), delta.y.abs()) {
+            (0, 1) | (1, 0) => can_enter(to),
+            // Diagonal steps may not cut corners around walls
+            (1, 1) => {
+                self.diagonal
+                    && can_enter(to)
+                    && can_enter(Point::new(to.x, from.y))
+                    && can_enter(Point::new(from.x, to.y))
+            }
+            _ => false,
+        }
+    }
+
+    // Offsets to all neighbouring tiles which can be stepped to
+    pub fn directions(&self) -> impl Iterator<Item = Point> {
+        let diagonal_deltas = if self.diagonal {
+            &DIAGONAL_DELTAS[..]
+        } else {
+            &[]
+        };
+        ORTHOGONAL_DELTAS
+            .into_iter()
+            .chain(diagonal_deltas.iter().copied())
+    }
+
+    pub fn exits(
+        &self,
+        from: Point,
+        mut tile_cost: impl FnMut(Point) -> Option<f32>,
+    ) -> SmallVec<[(Point, f32); 10]> {
+        let mut exits = SmallVec::new();
+        for delta in self.directions() {
+            let distance = if delta.x != 0 && delta.y != 0 {
+                std::f32::consts::SQRT_2
+            } else {
+                1.0
+            };
+            let to = from + delta;
+            if !self.allows_step(from, to, |p| tile_cost(p).is_some()) {
+                continue;
+            }
+            if let Some(cost) = tile_cost(to) {
+                exits.push((to, distance * cost));
+            }
+        }
+        exits
+    }
+}
+
+pub(super) fn apply_new_game_movement_rules(
+    mut movement_rules: ResMut<MovementRules>,
+    new_game_rules: Res<NewGameMovementRules>,
+) {
+    *movement_rules = new_game_rules.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALL: Point = Point::constant(1, 0);
+
+    fn open_except_wall(p: Point) -> bool {
+        p != WALL
+    }
+
+    #[test]
+    fn orthogonal_rules_forbid_diagonal_steps() {
+        let rules = MovementRules { diagonal: false };
+        let from = Point::new(0, 0);
+        assert!(rules.allows_step(from, Point::new(0, 1), |_| true));
+        assert!(!rules.allows_step(from, Point::new(1, 1), |_| true));
+        assert!(!rules.allows_step(from, Point::new(0, 2), |_| true));
+        assert!(!rules.allows_step(from, Point::new(1, 0), open_except_wall));
+    }
+
+    #[test]
+    fn diagonal_steps_may_not_cut_corners() {
+        let rules = MovementRules { diagonal: true };
+        let from = Point::new(0, 0);
+        assert!(rules.allows_step(from, Point::new(-1, 1), open_except_wall));
+        assert!(!rules.allows_step(from, Point::new(1, 1), open_except_wall));
+        assert!(!rules.allows_step(from, Point::new(1, -1), open_except_wall));
+    }
+
+    #[test]
+    fn exits_follow_the_rules() {
+        let from = Point::new(0, 0);
+        let cost = |p: Point| open_except_wall(p).then_some(1.0);
+
+        let orthogonal = MovementRules { diagonal: false }.exits(from, cost);
+        assert_eq!(orthogonal.len(), 3);
+        assert!(orthogonal.iter().all(|&(_, cost)| cost == 1.0));
+
+        let diagonal = MovementRules { diagonal: true }.exits(from, cost);
+        let destinations: Vec<_> = diagonal.iter().map(|&(to, _)| to).collect();
+        assert_eq!(diagonal.len(), 5);
+        assert!(!destinations.contains(&WALL));
+        assert!(!destinations.contains(&Point::new(1, 1)));
+        assert!(!destinations.contains(&Point::new(1, -1)));
+        let (_, cost) = diagonal
+            .iter()
+            .find(|&&(to, _)| to == Point::new(-1, -1))
+            .unwrap();
+        assert_eq!(*cost, std::f32::consts::SQRT_2);
+    }
+}
@@ -1,11 +1,12 @@
 use crate::prelude::*;
 use bevy::ecs::system::SystemParam;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 #[derive(SystemParam)]
 pub struct TileMapQuery<'w, 's> {
     pub map_query: MapQuery<'w, 's>,
     pub tile_query: Query<'w, 's, (&'static TilePos, &'static TileType)>,
+    pub movement_rules: Res<'w, MovementRules>,
 }
 
 impl TileMapQuery<'_, '_> {
@@ -16,33 +17,58 @@ impl TileMapQuery<'_, '_> {
             .unwrap()
     }
 
-    pub fn can_enter_tile(&mut self, &p: &Position) -> bool {
-        if let Ok(tile_pos) = p.try_into() {
-            self.map_query
-                .get_tile_entity(tile_pos, MAP_ID, MAP_LAYER_ID)
-                .map(|tile_entity| {
-                    self.tile_query
-                        .get_component::<TileType>(tile_entity)
-                        .map(|tile_type| *tile_type == TileType::Floor)
-                        .unwrap_or(false)
-                })
-                .unwrap_or(false)
-        } else {
-            false
-        }
+    pub fn tile_type(&mut self, &p: &Position) -> Option<TileType> {
+        let tile_pos = p.try_into().ok()?;
+        let tile_entity = self
+            .map_query
+            .get_tile_entity(tile_pos, MAP_ID, MAP_LAYER_ID)
+            .ok()?;
+        self.tile_query
+            .get_component::<TileType>(tile_entity)
+            .ok()
+            .cloned()
+    }
+
+    pub fn movement_cost(&mut self, p: &Position, can_swim: bool) -> Option<f32> {
+        self.tile_type(p)?.movement_cost(can_swim)
+    }
+
+    // Whether the tile can be entered, possibly after opening it first
+    pub fn can_enter_tile(&mut self, p: &Position, can_swim: bool) -> bool {
+        self.movement_cost(p, can_swim).is_some()
+    }
+
+    // Whether a mob at `from` can step onto or bump into whatever is at `to`
+    pub fn can_reach(&mut self, from: &Position, to: &Position, can_swim: bool) -> bool {
+        let movement_rules = *self.movement_rules;
+        let to_point: Point = (*to).into();
+        movement_rules.allows_step((*from).into(), to_point, |p| {
+            p == to_point || self.can_enter_tile(&p.into(), can_swim)
+        })
     }
 }
 
 pub struct MapAdapter<'w, 's> {
     cell: RefCell<TileMapQuery<'w, 's>>,
+    can_swim: Cell<bool>,
 }
 
 impl<'w, 's> MapAdapter<'w, 's> {
     pub fn new(tile_map_query: TileMapQuery<'w, 's>) -> Self {
         Self {
             cell: RefCell::new(tile_map_query),
+            can_swim: Cell::new(false),
         }
     }
+
+    pub fn tile_type(&self, p: &Position) -> Option<TileType> {
+        self.cell.borrow_mut().tile_type(p)
+    }
+
+    // Paths are planned for walkers unless told otherwise
+    pub fn set_can_swim(&self, can_swim: bool) {
+        self.can_swim.set(can_swim);
+    }
 }
 
 impl BaseMap for MapAdapter<'_, '_> {
@@ -53,43 +79,28 @@ impl BaseMap for MapAdapter<'_, '_> {
             .map_query
             .get_tile_entity(tile_position.try_into().unwrap(), MAP_ID, MAP_LAYER_ID)
             .unwrap();
-        *tile_map_query
+        tile_map_query
             .tile_query
             .get_component::<TileType>(tile_entity)
             .unwrap()
-            == TileType::Wall
+            .is_opaque()
     }
 
     fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
-        let tile_position: Position = self.index_to_point2d(idx).into();
-        let neighbor_positions: SmallVec<[Position; 10]> = {
+        let location = self.index_to_point2d(idx);
+        let exits = {
             let mut tile_map_query = self.cell.borrow_mut();
-            tile_map_query
-                .map_query
-                .get_tile_neighbors(tile_position.try_into().unwrap(), MAP_ID, MAP_LAYER_ID)
-                .into_iter()
-                .take(4)
-                .filter_map(|result| result.ok())
-                .filter(|entity| {
-                    *tile_map_query
-                        .tile_query
-                        .get_component::<TileType>(*entity)
-                        .unwrap()
-                        == TileType::Floor
-                })
-                .map(|entity| {
-                    tile_map_query
-                        .tile_query
-                        .get_component::<TilePos>(entity)
-                        .unwrap()
-                        .clone()
-                        .into()
-                })
-                .collect()
+            let movement_rules = *tile_map_query.movement_rules;
+            let can_swim = self.can_swim.get();
+            movement_rules.exits(location, |p| {
+                tile_map_query
+                    .tile_type(&p.into())
+                    .and_then(|tile_type| tile_type.path_cost(can_swim))
+            })
         };
-        neighbor_positions
+        exits
             .into_iter()
-            .map(|pos| (self.point2d_to_index(pos.into()), 1.0))
+            .map(|(p, cost)| (self.point2d_to_index(p), cost))
             .collect()
     }
 
@@ -8,17 +8,61 @@ impl Plugin for PlayerInputPlugin {
         app.add_system_to_stage(
             CoreStage::PreUpdate,
             player_input.run_in_state(TurnState::AwaitingInput),
-        );
+        )
+        .add_system(
+            start_travelling
+                .run_in_state(TurnState::AwaitingInput)
+                .after("mouse_click_tile_interaction"),
+        )
+        .add_system(stop_exploring_on_pickup);
     }
 }
 
+#[derive(Component, Debug)]
+pub struct Travelling {
+    plan: TravelPlan,
+    hitpoints: u32,
+    // Only hostiles coming into view interrupt travelling, not those already seen when setting off
+    hostiles_in_view: Vec<Entity>,
+}
+
+#[derive(Debug)]
+enum TravelPlan {
+    // Remaining steps in reverse order, so that the next step can be popped off the end
+    Path(Vec<Position>),
+    Explore,
+}
+
 fn player_input(
     mut keyboard_input_events: EventReader<KeyboardInput>,
+    input_bindings: Res<InputBindings>,
     mut msgs: EventWriter<WantsToMove>,
-    player_query: Query<(Entity, &Position), With<Player>>,
+    mut close_door_msgs: EventWriter<WantsToCloseDoor>,
+    mut log_msgs: EventWriter<LogMessage>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Position,
+            &FieldOfView,
+            &Health,
+            Option<&mut Travelling>,
+        ),
+        With<Player>,
+    >,
+    hostile_query: Query<(Entity, &Position, &Name), With<Hostile>>,
+    mut tile_map_query: TileMapQuery,
+    tile_query: Query<(&TilePos, &Tile, &TileType)>,
     mut commands: Commands,
 ) {
-    if let Some((player_entity, &Position(player_vec))) = player_query.iter().next() {
+    if let Some((player_entity, &Position(player_vec), player_fov, player_health, travelling)) =
+        player_query.iter_mut().next()
+    {
+        let hostiles_in_view: Vec<_> = hostile_query
+            .iter()
+            .filter(|(_, &pos, _)| player_fov.can_see(pos))
+            .collect();
+
+        let mut key_pressed = false;
         for event in keyboard_input_events.iter() {
             if let KeyboardInput {
                 key_code: Some(key),
@@ -26,29 +70,249 @@ fn player_input(
                 ..
             } = event
             {
-                use KeyCode::*;
-                let next_state = match key {
-                    W | A | S | D => {
-                        let delta = match key {
-                            W => ivec2(0, 1),
-                            A => ivec2(-1, 0),
-                            S => ivec2(0, -1),
-                            D => ivec2(1, 0),
-                            _ => unreachable!(),
-                        };
+                use InputAction::*;
+                let next_state = match input_bindings.action(*key) {
+                    Some(Move(direction))
+                        if direction.is_diagonal() && !tile_map_query.movement_rules.diagonal =>
+                    {
+                        continue
+                    }
+                    Some(Move(direction)) => {
                         msgs.send(WantsToMove {
                             entity: player_entity,
-                            destination: Position(player_vec + delta),
+                            destination: Position(player_vec + direction.delta()),
                         });
                         NextState(TurnState::PlayerTurn)
                     }
-                    Space => NextState(TurnState::PlayerTurn),
-                    Escape => NextState(TurnState::Pause),
+                    Some(Wait) => NextState(TurnState::PlayerTurn),
+                    Some(Pause) => NextState(TurnState::Pause),
+                    Some(CloseDoors) => {
+                        let movement_rules = *tile_map_query.movement_rules;
+                        let doors: Vec<_> = movement_rules
+                            .directions()
+                            .map(|delta| Position(player_vec + ivec2(delta.x, delta.y)))
+                            .filter(|pos| tile_map_query.tile_type(pos) == Some(TileType::DoorOpen))
+                            .collect();
+                        if doors.is_empty() {
+                            log_msgs.send(LogMessage::info("There is no open door nearby"));
+                            continue;
+                        }
+                        for position in doors {
+                            close_door_msgs.send(WantsToCloseDoor {
+                                entity: player_entity,
+                                position,
+                            });
+                        }
+                        NextState(TurnState::PlayerTurn)
+                    }
+                    Some(Explore) => {
+                        commands.entity(player_entity).insert(Travelling {
+                            plan: TravelPlan::Explore,
+                            hitpoints: player_health.hitpoints(),
+                            hostiles_in_view: hostiles_in_view
+                                .iter()
+                                .map(|&(entity, ..)| entity)
+                                .collect(),
+                        });
+                        continue;
+                    }
                     _ => continue,
                 };
 
+                key_pressed = true;
                 commands.insert_resource(next_state);
             }
         }
+
+        if let Some(mut travelling) = travelling {
+            // Any key press takes back control from travelling
+            if key_pressed {
+                commands.entity(player_entity).remove::<Travelling>();
+                return;
+            }
+
+            let spotted = hostiles_in_view
+                .iter()
+                .find(|(entity, ..)| !travelling.hostiles_in_view.contains(entity))
+                .map(|&(_, _, name)| name);
+            travelling.hostiles_in_view = hostiles_in_view
+                .iter()
+                .map(|&(entity, ..)| entity)
+                .collect();
+
+            let next_step = if let Some(name) = spotted {
+                log_msgs.send(LogMessage::info(format!("You spot the {}", name)));
+                None
+            } else if player_health.hitpoints() < travelling.hitpoints {
+                None
+            } else {
+                match &mut travelling.plan {
+                    TravelPlan::Path(path) => {
+                        // Doors on the way are bumped open before stepping through them
+                        let step = path.last().copied();
+                        let tile_type = step.and_then(|step| tile_map_query.tile_type(&step));
+                        if tile_type != Some(TileType::DoorClosed) {
+                            path.pop();
+                        }
+
+                        // Stairs on the way would take the player down before they arrive
+                        if tile_type == Some(TileType::Stairs) && !path.is_empty() {
+                            log_msgs.send(LogMessage::info("You stop at the stairs"));
+                            None
+                        } else {
+                            step
+                        }
+                    }
+                    TravelPlan::Explore => {
+                        let map = MapAdapter::new(tile_map_query);
+                        let step = next_exploration_step(Position(player_vec), &map, &tile_query);
+                        let onto_stairs =
+                            step.and_then(|step| map.tile_type(&step)) == Some(TileType::Stairs);
+                        if step.is_none() {
+                            log_msgs.send(LogMessage::info("There is nothing left to explore"));
+                            None
+                        } else if onto_stairs {
+                            log_msgs.send(LogMessage::info("You stop at the stairs"));
+                            None
+                        } else {
+                            step
+                        }
+                    }
+                }
+            };
+
+            if let Some(destination) = next_step {
+                travelling.hitpoints = player_health.hitpoints();
+                msgs.send(WantsToMove {
+                    entity: player_entity,
+                    destination,
+                });
+                commands.insert_resource(NextState(TurnState::PlayerTurn));
+            } else {
+                commands.entity(player_entity).remove::<Travelling>();
+            }
+        }
     }
 }
+
+fn start_travelling(
+    mut tile_evr: EventReader<TileInteraction>,
+    mut commands: Commands,
+    mut tile_map_query: TileMapQuery,
+    tile_query: Query<&Tile>,
+    ui_query: Query<&Interaction>,
+    player_query: Query<(Entity, &Position, &FieldOfView, &Health), With<Player>>,
+    hostile_query: Query<(Entity, &Position), With<Hostile>>,
+) {
+    // Clicks on UI elements are not meant for the map underneath
+    if ui_query
+        .iter()
+        .any(|&interaction| interaction != Interaction::None)
+    {
+        return;
+    }
+
+    if let Some(TileInteraction(tile_pos)) = tile_evr.iter().last() {
+        let (player_entity, &player_pos, player_fov, player_health) = player_query.single();
+        let destination: Position = (*tile_pos).into();
+
+        // Only travel to tiles the player has seen before and can walk on
+        let is_revealed = tile_map_query
+            .map_query
+            .get_tile_entity(*tile_pos, MAP_ID, MAP_LAYER_ID)
+            .ok()
+            .and_then(|tile_entity| tile_query.get(tile_entity).ok())
+            .map(|tile| tile.visible)
+            .unwrap_or(false);
+        if !is_revealed || !tile_map_query.can_enter_tile(&destination, false) {
+            return;
+        }
+
+        let map = MapAdapter::new(tile_map_query);
+        let path = a_star_search(
+            map.point2d_to_index(player_pos.into()),
+            map.point2d_to_index(destination.into()),
+            &map,
+        );
+
+        if path.success {
+            commands.entity(player_entity).insert(Travelling {
+                plan: TravelPlan::Path(
+                    path.steps
+                        .into_iter()
+                        .skip(1)
+                        .rev()
+                        .map(|idx| map.index_to_point2d(idx).into())
+                        .collect(),
+                ),
+                hitpoints: player_health.hitpoints(),
+                hostiles_in_view: hostile_query
+                    .iter()
+                    .filter(|(_, &pos)| player_fov.can_see(pos))
+                    .map(|(entity, _)| entity)
+                    .collect(),
+            });
+        }
+    }
+}
+
+// Runs outside of AwaitingInput, as pickups happen during the player's turn
+fn stop_exploring_on_pickup(
+    mut commands: Commands,
+    mut picked_up_msgs: EventReader<PickedUpItem>,
+    travelling_query: Query<&Travelling>,
+) {
+    for &PickedUpItem { entity } in picked_up_msgs.iter() {
+        if let Ok(Travelling {
+            plan: TravelPlan::Explore,
+            ..
+        }) = travelling_query.get(entity)
+        {
+            commands.entity(entity).remove::<Travelling>();
+        }
+    }
+}
+
+fn next_exploration_step(
+    player_pos: Position,
+    map: &MapAdapter,
+    tile_query: &Query<(&TilePos, &Tile, &TileType)>,
+) -> Option<Position> {
+    let mut revealed = vec![false; MAP_WIDTH * MAP_HEIGHT];
+    let mut walkable = vec![false; MAP_WIDTH * MAP_HEIGHT];
+    for (&tile_pos, tile, tile_type) in tile_query.iter() {
+        let idx = map.point2d_to_index(Position::from(tile_pos).into());
+        revealed[idx] = tile.visible;
+        walkable[idx] = tile_type.movement_cost(false).is_some();
+    }
+
+    // Head for the closest unrevealed tile which could be walked onto or is at the edge of
+    // the known floor, rather than towards walls or areas which can't be reached
+    let unrevealed_tiles: Vec<_> = (0..revealed.len())
+        .filter(|&idx| !revealed[idx])
+        .filter(|&idx| {
+            let p = map.index_to_point2d(idx);
+            walkable[idx]
+                || [
+                    Point::new(-1, 0),
+                    Point::new(1, 0),
+                    Point::new(0, -1),
+                    Point::new(0, 1),
+                ]
+                .into_iter()
+                .map(|delta| p + delta)
+                .filter(|&neighbor| map.in_bounds(neighbor))
+                .map(|neighbor| map.point2d_to_index(neighbor))
+                .any(|neighbor| revealed[neighbor] && walkable[neighbor])
+        })
+        .collect();
+    let dijkstra_map = DijkstraMap::new(MAP_WIDTH, MAP_HEIGHT, &unrevealed_tiles, map, 1024.0);
+
+    let player_idx = map.point2d_to_index(player_pos.into());
+    if dijkstra_map.map[player_idx] == f32::MAX {
+        return None;
+    }
+
+    DijkstraMap::find_lowest_exit(&dijkstra_map, player_idx, map)
+        .map(|idx| map.index_to_point2d(idx).into())
+}
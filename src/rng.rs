@@ -0,0 +1,184 @@
+use crate::prelude::*;
+use rand::{thread_rng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+pub struct GameRngPlugin;
+
+impl Plugin for GameRngPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameRng::new(seed_from_args()))
+            .add_enter_system(TurnState::NewGame, reseed.before("spawn_map_layer"));
+    }
+}
+
+// Uses the same generator as rand's StdRng, whose state can't be saved
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GameRng {
+    #[serde(skip)]
+    fixed_seed: Option<u64>,
+    seed: u64,
+    rng: ChaCha12Rng,
+}
+
+impl GameRng {
+    pub fn new(fixed_seed: Option<u64>) -> Self {
+        let seed = fixed_seed.unwrap_or_else(|| thread_rng().gen());
+        GameRng {
+            fixed_seed,
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Continues a saved game exactly where its random sequence left off
+    pub fn restore(&mut self, saved: GameRng) {
+        self.seed = saved.seed;
+        self.rng = saved.rng;
+    }
+
+    // Levels are generated from their own stream, so they don't depend on how many other
+    // random draws happened earlier in the game
+    pub fn level_rng(&self, level: usize) -> GameRng {
+        let mut rng = ChaCha12Rng::seed_from_u64(self.seed);
+        rng.set_stream(level as u64 + 1);
+        GameRng {
+            fixed_seed: None,
+            seed: self.seed,
+            rng,
+        }
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+// Dice written in the usual notation, e.g. "2d6+1"
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Dice {
+    pub count: u32,
+    pub sides: u32,
+    pub bonus: i32,
+}
+
+impl Dice {
+    pub fn roll(&self, rng: &mut impl Rng) -> i32 {
+        let total: u32 = (0..self.count)
+            .map(|_| rng.gen_range(1..=self.sides.max(1)))
+            .sum();
+        total as i32 + self.bonus
+    }
+}
+
+impl FromStr for Dice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid dice {:?}, expected e.g. \"2d6+1\"", s);
+        let (count, rest) = s.trim().split_once('d').ok_or_else(invalid)?;
+        let (sides, bonus) = match rest.find(['+', '-']) {
+            Some(idx) => rest.split_at(idx),
+            None => (rest, "+0"),
+        };
+        let count = if count.is_empty() {
+            1
+        } else {
+            count.parse().map_err(|_| invalid())?
+        };
+        Ok(Dice {
+            count,
+            sides: sides.parse().map_err(|_| invalid())?,
+            bonus: bonus
+                .trim_start_matches('+')
+                .parse()
+                .map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl TryFrom<String> for Dice {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Dice> for String {
+    fn from(dice: Dice) -> Self {
+        dice.to_string()
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+        if self.bonus != 0 {
+            write!(f, "{:+}", self.bonus)?;
+        }
+        Ok(())
+    }
+}
+
+fn seed_from_args() -> Option<u64> {
+    std::env::args()
+        .skip_while(|arg| arg != "--seed")
+        .nth(1)
+        .and_then(|seed| seed.parse().ok())
+}
+
+fn reseed(mut rng: ResMut<GameRng>) {
+    // Draw a fresh seed for each new game unless one was passed via `--seed`
+    *rng = GameRng::new(rng.fixed_seed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dice_parse() {
+        let dice = |count, sides, bonus| Dice {
+            count,
+            sides,
+            bonus,
+        };
+        assert_eq!("d6".parse(), Ok(dice(1, 6, 0)));
+        assert_eq!("2d6-1".parse(), Ok(dice(2, 6, -1)));
+        assert_eq!("3d4+2".parse(), Ok(dice(3, 4, 2)));
+        assert!("2x6".parse::<Dice>().is_err());
+        assert!("2d".parse::<Dice>().is_err());
+    }
+
+    #[test]
+    fn dice_display_round_trips() {
+        for s in ["d6", "2d6-1", "3d4+2", "1d20"] {
+            let dice: Dice = s.parse().unwrap();
+            assert_eq!(dice.to_string().parse(), Ok(dice));
+        }
+        assert_eq!("d6".parse::<Dice>().unwrap().to_string(), "1d6");
+        assert_eq!("2d6-1".parse::<Dice>().unwrap().to_string(), "2d6-1");
+    }
+}
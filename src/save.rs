@@ -0,0 +1,449 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+const SAVE_FILE: &str = "savegame.ron";
+
+// Browsers don't give access to a file system to keep the save file in
+pub const SAVES_ENABLED: bool = cfg!(not(target_arch = "wasm32"));
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WantsToSave>().add_event::<WantsToLoad>();
+        if SAVES_ENABLED {
+            app.add_system(save_game.run_in_state(TurnState::Pause))
+                .add_system(load_game.run_in_state(TurnState::Pause));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct WantsToSave;
+
+#[derive(Debug)]
+pub struct WantsToLoad;
+
+// Games are only saved from the pause menu, which is opened while awaiting player input, so
+// loaded games always continue from there
+#[derive(Debug, Deserialize, Serialize)]
+struct SaveGame {
+    rng: GameRng,
+    level: usize,
+    biome: Option<BiomeData>,
+    movement_rules: MovementRules,
+    tiles: Vec<SavedTile>,
+    mobs: Vec<SavedMob>,
+    items: Vec<SavedItem>,
+    memory: Vec<SavedMemory>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct SavedTile {
+    tile_type: TileType,
+    revealed: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct SavedMob {
+    player: bool,
+    name: String,
+    glyph: usize,
+    position: (i32, i32),
+    health: Health,
+    combat: CombatStats,
+    fov_radius: i32,
+    speed: i32,
+    energy: i32,
+    status_effects: StatusEffects,
+    experience: Option<Experience>,
+    xp_reward: u32,
+    gold: u32,
+    loot: Option<String>,
+    behaviors: Vec<BehaviorData>,
+}
+
+// Entities remembered by the player, referred to by their index among the saved mobs or items
+#[derive(Debug, Deserialize, Serialize)]
+struct SavedMemory {
+    of: SavedMemoryOf,
+    position: (i32, i32),
+    glyph: SavedGlyph,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+enum SavedMemoryOf {
+    Mob(usize),
+    Item(usize),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+enum SavedGlyph {
+    Atlas(usize),
+    Image(String),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct SavedItem {
+    name: String,
+    icon: Option<String>,
+    amulet: bool,
+    position: Option<(i32, i32)>,
+    carried_by: Option<usize>,
+    effects: Vec<EffectData>,
+    equipment: Option<EquipmentData>,
+    equipped: bool,
+    gold: Option<u32>,
+}
+
+fn save_game(
+    mut save_msgs: EventReader<WantsToSave>,
+    tile_query: Query<(&TilePos, &TileType, &Tile)>,
+    mob_query: Query<
+        (
+            Entity,
+            &Position,
+            &Health,
+            &CombatStats,
+            &FieldOfView,
+            &Speed,
+            &Energy,
+            &StatusEffects,
+            &TextureAtlasSprite,
+            Option<&Experience>,
+            Option<&ExperienceReward>,
+            Option<&Name>,
+            Option<&Player>,
+            Option<&ChasingPlayer>,
+            Option<&Swimmer>,
+        ),
+        With<Mob>,
+    >,
+    loot_query: Query<(Option<&Gold>, Option<&LootTable>), With<Mob>>,
+    item_query: Query<
+        (
+            Entity,
+            &Name,
+            Option<&Handle<Image>>,
+            Option<&AmuletOfYala>,
+            Option<&Position>,
+            Option<&CarriedBy>,
+            Option<&Effects>,
+            Option<&Equipment>,
+            Option<&Equipped>,
+            Option<&Gold>,
+        ),
+        With<Item>,
+    >,
+    current_level: Res<CurrentLevel>,
+    current_biome: Res<CurrentBiome>,
+    movement_rules: Res<MovementRules>,
+    memory_query: Query<&MapMemory, With<Player>>,
+    rng: Res<GameRng>,
+    asset_server: Res<AssetServer>,
+) {
+    if save_msgs.iter().count() == 0 {
+        return;
+    }
+
+    let mut tiles: Vec<_> = std::iter::repeat_with(|| SavedTile {
+        tile_type: TileType::Wall,
+        revealed: false,
+    })
+    .take(MAP_WIDTH * MAP_HEIGHT)
+    .collect();
+    for (&TilePos(x, y), &tile_type, tile) in tile_query.iter() {
+        tiles[y as usize * MAP_WIDTH + x as usize] = SavedTile {
+            tile_type,
+            revealed: tile.visible,
+        };
+    }
+
+    let mob_entities: Vec<_> = mob_query.iter().map(|(entity, ..)| entity).collect();
+    let mobs = mob_query
+        .iter()
+        .map(
+            |(
+                entity,
+                &Position(pos),
+                health,
+                combat,
+                fov,
+                speed,
+                energy,
+                status_effects,
+                sprite,
+                experience,
+                reward,
+                name,
+                player,
+                chasing,
+                swimmer,
+            )| {
+                let (gold, loot) = loot_query.get(entity).unwrap_or_default();
+                SavedMob {
+                    player: player.is_some(),
+                    name: name.map(|name| name.to_string()).unwrap_or_default(),
+                    glyph: sprite.index,
+                    position: (pos.x, pos.y),
+                    health: health.clone(),
+                    combat: combat.clone(),
+                    fov_radius: fov.radius,
+                    speed: speed.0,
+                    energy: energy.0,
+                    status_effects: status_effects.clone(),
+                    experience: experience.cloned(),
+                    xp_reward: reward.map(|&ExperienceReward(xp)| xp).unwrap_or_default(),
+                    gold: gold.map(|&Gold(gold)| gold).unwrap_or_default(),
+                    loot: loot.and_then(|LootTable(loot)| loot.clone()),
+                    behaviors: chasing
+                        .map(|_| BehaviorData::ChasingPlayer)
+                        .into_iter()
+                        .chain(swimmer.map(|_| BehaviorData::Swimming))
+                        .collect(),
+                }
+            },
+        )
+        .collect();
+
+    let items = item_query
+        .iter()
+        .map(
+            |(_, name, image, amulet, position, carried_by, effects, equipment, equipped, gold)| {
+                SavedItem {
+                    name: name.to_string(),
+                    icon: image
+                        .and_then(|image| asset_server.get_handle_path(image))
+                        .map(|path| path.path().to_string_lossy().into_owned()),
+                    amulet: amulet.is_some(),
+                    position: position.map(|&Position(pos)| (pos.x, pos.y)),
+                    carried_by: carried_by.and_then(|CarriedBy(carrier)| {
+                        mob_entities.iter().position(|entity| entity == carrier)
+                    }),
+                    effects: effects
+                        .map(|Effects(effects)| effects.clone())
+                        .unwrap_or_default(),
+                    equipment: equipment.map(|Equipment(equipment)| equipment.clone()),
+                    equipped: equipped.is_some(),
+                    gold: gold.map(|&Gold(gold)| gold),
+                }
+            },
+        )
+        .collect();
+
+    let item_entities: Vec<_> = item_query.iter().map(|(entity, ..)| entity).collect();
+    let memory = memory_query
+        .iter()
+        .flat_map(|memory| memory.entities.iter())
+        .filter_map(|(entity, remembered)| {
+            let of = match mob_entities.iter().position(|mob| mob == entity) {
+                Some(idx) => SavedMemoryOf::Mob(idx),
+                None => SavedMemoryOf::Item(item_entities.iter().position(|item| item == entity)?),
+            };
+            let glyph = match &remembered.glyph {
+                Glyph::Atlas(index) => SavedGlyph::Atlas(*index),
+                Glyph::Image(image) => SavedGlyph::Image(
+                    asset_server
+                        .get_handle_path(image)?
+                        .path()
+                        .to_string_lossy()
+                        .into_owned(),
+                ),
+            };
+            let Position(pos) = remembered.position;
+            Some(SavedMemory {
+                of,
+                position: (pos.x, pos.y),
+                glyph,
+            })
+        })
+        .collect();
+
+    let CurrentLevel(level) = *current_level;
+    let CurrentBiome(biome) = current_biome.as_ref();
+    let save_game = SaveGame {
+        rng: rng.clone(),
+        level,
+        biome: biome.clone(),
+        movement_rules: *movement_rules,
+        tiles,
+        mobs,
+        items,
+        memory,
+    };
+
+    match ron::ser::to_string_pretty(&save_game, Default::default()) {
+        Ok(serialized) => {
+            if let Err(err) = fs::write(SAVE_FILE, serialized) {
+                error!("Failed to write save file {}: {}", SAVE_FILE, err);
+            }
+        }
+        Err(err) => error!("Failed to serialize game: {}", err),
+    }
+}
+
+fn load_game(
+    mut load_msgs: EventReader<WantsToLoad>,
+    mut commands: Commands,
+    mut map_query: MapQuery,
+    entity_query: Query<Entity, Or<(With<Mob>, With<Item>)>>,
+    texture_atlas: Res<DefaultTextureAtlas>,
+    asset_server: Res<AssetServer>,
+    mut rng: ResMut<GameRng>,
+) {
+    if load_msgs.iter().count() == 0 {
+        return;
+    }
+
+    let save_game: SaveGame = match fs::read_to_string(SAVE_FILE)
+        .map_err(|err| err.to_string())
+        .and_then(|serialized| ron::from_str(&serialized).map_err(|err| err.to_string()))
+    {
+        Ok(save_game) => save_game,
+        Err(err) => {
+            error!("Failed to load save file {}: {}", SAVE_FILE, err);
+            return;
+        }
+    };
+
+    // Replace the current map and all of its entities
+    map_query.despawn(&mut commands, MAP_ID);
+    for entity in entity_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    build_map_layer(
+        &mut commands,
+        &mut map_query,
+        asset_server.as_ref(),
+        save_game.biome.as_ref(),
+        save_game
+            .tiles
+            .iter()
+            .map(|tile| (tile.tile_type, tile.revealed)),
+    );
+    commands.insert_resource(CurrentBiome(save_game.biome));
+    commands.insert_resource(CurrentLevel(save_game.level));
+    commands.insert_resource(save_game.movement_rules);
+    rng.restore(save_game.rng);
+
+    let player_idx = save_game.mobs.iter().position(|mob| mob.player);
+    let mob_entities: Vec<_> = save_game
+        .mobs
+        .into_iter()
+        .map(|mob| {
+            let position = Position::new(mob.position.0, mob.position.1);
+            let mut mob_commands = if mob.player {
+                commands.spawn_bundle(PlayerBundle::new(
+                    position,
+                    &PlayerData {
+                        glyph: mob.glyph,
+                        health: mob.health.hitpoints(),
+                        combat: mob.combat.clone(),
+                        fov_radius: mob.fov_radius,
+                        speed: mob.speed,
+                    },
+                    texture_atlas.as_ref(),
+                ))
+            } else {
+                commands.spawn_bundle(HostileMobBundle::new(
+                    position,
+                    &MobData {
+                        name: mob.name,
+                        glyph: mob.glyph,
+                        health: mob.health.hitpoints(),
+                        combat: mob.combat.clone(),
+                        xp: mob.xp_reward,
+                        loot: mob.loot.clone(),
+                        fov_radius: mob.fov_radius,
+                        speed: mob.speed,
+                        frequency: 0.0,
+                        behaviors: mob.behaviors.clone(),
+                    },
+                    texture_atlas.as_ref(),
+                ))
+            };
+            if let Some(experience) = mob.experience {
+                mob_commands.insert(experience);
+            }
+            if mob.player {
+                mob_commands.insert(Gold(mob.gold));
+            }
+            mob_commands
+                .insert(mob.health)
+                .insert(mob.combat)
+                .insert(Speed(mob.speed))
+                .insert(Energy(mob.energy))
+                .insert(mob.status_effects);
+            for behavior in &mob.behaviors {
+                apply_behavior_to_mob(&mut mob_commands, behavior);
+            }
+            mob_commands.id()
+        })
+        .collect();
+
+    let mut item_entities = Vec::new();
+    for item in save_game.items {
+        let position = item
+            .position
+            .map(|(x, y)| Position::new(x, y))
+            .unwrap_or_default();
+        let mut item_commands = if item.amulet {
+            commands.spawn_bundle(AmuletBundle::new(position, texture_atlas.as_ref()))
+        } else {
+            commands.spawn_bundle(ItemBundle::new(
+                &item.name,
+                position,
+                asset_server.load(item.icon.unwrap_or_default().as_str()),
+            ))
+        };
+        item_commands.insert(Effects(item.effects));
+        if let Some(equipment) = item.equipment {
+            item_commands.insert(Equipment(equipment));
+        }
+        if item.equipped {
+            item_commands.insert(Equipped);
+        }
+        if let Some(gold) = item.gold {
+            item_commands.insert(Gold(gold));
+        }
+        if let Some(&carrier) = item.carried_by.and_then(|idx| mob_entities.get(idx)) {
+            item_commands
+                .remove::<Position>()
+                .insert(Visibility { is_visible: false })
+                .insert(CarriedBy(carrier));
+        }
+        item_entities.push(item_commands.id());
+    }
+
+    if let Some(&player_entity) = player_idx.and_then(|idx| mob_entities.get(idx)) {
+        let entities = save_game
+            .memory
+            .into_iter()
+            .filter_map(|saved| {
+                let entity = match saved.of {
+                    SavedMemoryOf::Mob(idx) => mob_entities.get(idx),
+                    SavedMemoryOf::Item(idx) => item_entities.get(idx),
+                }?;
+                let glyph = match saved.glyph {
+                    SavedGlyph::Atlas(index) => Glyph::Atlas(index),
+                    SavedGlyph::Image(path) => Glyph::Image(asset_server.load(path.as_str())),
+                };
+                let (x, y) = saved.position;
+                Some((
+                    *entity,
+                    RememberedEntity {
+                        position: Position::new(x, y),
+                        glyph,
+                    },
+                ))
+            })
+            .collect();
+        commands
+            .entity(player_entity)
+            .insert(MapMemory { entities });
+    }
+
+    commands.insert_resource(NextState(TurnState::AwaitingInput));
+}
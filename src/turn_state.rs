@@ -1,9 +1,11 @@
 use crate::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum TurnState {
     Loading,
     NewGame,
+    NextLevel,
     AwaitingInput,
     PlayerTurn,
     MonsterTurn,
@@ -25,19 +27,38 @@ fn end_turn(
     turn_state: Res<CurrentState<TurnState>>,
     mut commands: Commands,
     amulet_query: Query<Entity, (With<AmuletOfYala>, With<CarriedBy>)>,
+    player_query: Query<&Position, (With<Player>, Changed<Position>)>,
+    mut tile_map_query: TileMapQuery,
+    mut current_level: ResMut<CurrentLevel>,
+    mut log_msgs: EventWriter<LogMessage>,
+    player_energy_query: Query<&Energy, With<Player>>,
 ) {
     use TurnState::*;
 
     let next_state = if let Some(amulet_entity) = amulet_query.iter().next() {
         commands.entity(amulet_entity).despawn();
         Victory
+    } else if player_query
+        .iter()
+        .any(|pos| tile_map_query.tile_type(pos) == Some(TileType::Stairs))
+    {
+        current_level.0 += 1;
+        log_msgs.send(LogMessage::info("You descend the stairs"));
+        NextLevel
     } else {
         let CurrentState(current_state) = turn_state.as_ref();
         match current_state {
             Loading | AwaitingInput | Victory | Defeat | Pause => return,
             PlayerTurn => MonsterTurn,
-            MonsterTurn => AwaitingInput,
-            NewGame => AwaitingInput,
+            MonsterTurn => {
+                // Monsters keep acting until the player has gathered enough energy
+                if player_energy_query.iter().all(|energy| energy.is_ready()) {
+                    AwaitingInput
+                } else {
+                    return;
+                }
+            }
+            NewGame | NextLevel => AwaitingInput,
         }
     };
 
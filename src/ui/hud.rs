@@ -1,8 +1,14 @@
 use super::{UiState, UiStyles};
 use crate::prelude::*;
 use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy::input::{keyboard::KeyboardInput, mouse::MouseWheel, ElementState};
+use bevy::math::Rect;
 use bevy::ui::FocusPolicy;
 
+const VISIBLE_LOG_LINES: usize = 8;
+const XP_BAR: Color = Color::rgb(0.3, 0.6, 1.0);
+const EQUIPPED_ITEM: Color = Color::rgba(0.8, 0.7, 0.2, 0.5);
+
 pub struct HudPlugin;
 
 impl Plugin for HudPlugin {
@@ -14,8 +20,16 @@ impl Plugin for HudPlugin {
                     .run_in_state(UiState::Hud)
                     .with_system(update_fps_hud)
                     .with_system(update_health_hud)
+                    .with_system(update_experience_hud)
+                    .with_system(update_gold_hud)
+                    .with_system(update_status_effects_hud)
                     .with_system(add_newly_carried_item_to_inventory)
                     .with_system(inventory_item_interaction)
+                    .with_system(inventory_slot_hotkeys)
+                    .with_system(remove_spent_items_from_inventory)
+                    .with_system(highlight_equipped_items)
+                    .with_system(scroll_game_log)
+                    .with_system(update_game_log_hud)
                     .into(),
             );
     }
@@ -30,12 +44,32 @@ struct FpsText;
 #[derive(Component, Debug)]
 struct PlayerHealthText;
 
+#[derive(Component, Debug)]
+struct PlayerLevelText;
+
+#[derive(Component, Debug)]
+struct PlayerXpBar;
+
+#[derive(Component, Debug)]
+struct PlayerGoldText;
+
+#[derive(Component, Debug)]
+struct StatusEffectsBar;
+
 #[derive(Component, Debug)]
 struct InventoryBar;
 
 #[derive(Component, Debug)]
 struct RepresentsItem(Entity);
 
+#[derive(Component, Debug)]
+struct GameLogPanel;
+
+#[derive(Component, Debug, Default)]
+struct GameLogText {
+    scroll: usize,
+}
+
 fn set_up_hud(
     mut commands: Commands,
     styles: Res<UiStyles>,
@@ -88,23 +122,134 @@ fn set_up_hud(
                         })
                         .insert(FpsText);
                     parent
-                        .spawn_bundle(TextBundle {
-                            text: Text {
-                                sections: vec![
-                                    TextSection {
-                                        value: "Player Health: ".to_string(),
-                                        style: styles.text(),
-                                    },
-                                    TextSection {
-                                        value: String::new(),
-                                        style: styles.text(),
-                                    },
-                                ],
-                                alignment: Default::default(),
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            color: Color::NONE.into(),
+                            ..default()
+                        })
+                        .insert(StatusEffectsBar);
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                ..default()
                             },
+                            color: Color::NONE.into(),
                             ..default()
                         })
-                        .insert(PlayerHealthText);
+                        .with_children(|parent| {
+                            parent
+                                .spawn_bundle(TextBundle {
+                                    text: Text {
+                                        sections: vec![
+                                            TextSection {
+                                                value: "Player Health: ".to_string(),
+                                                style: styles.text(),
+                                            },
+                                            TextSection {
+                                                value: String::new(),
+                                                style: styles.text(),
+                                            },
+                                        ],
+                                        alignment: Default::default(),
+                                    },
+                                    ..default()
+                                })
+                                .insert(PlayerHealthText);
+                            parent
+                                .spawn_bundle(TextBundle {
+                                    style: Style {
+                                        margin: Rect {
+                                            left: Val::Px(16.0),
+                                            right: Val::Px(8.0),
+                                            ..default()
+                                        },
+                                        ..default()
+                                    },
+                                    text: Text::with_section(
+                                        String::new(),
+                                        styles.text(),
+                                        Default::default(),
+                                    ),
+                                    ..default()
+                                })
+                                .insert(PlayerLevelText);
+                            parent
+                                .spawn_bundle(NodeBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(100.0), Val::Px(10.0)),
+                                        margin: Rect {
+                                            right: Val::Px(8.0),
+                                            ..default()
+                                        },
+                                        ..default()
+                                    },
+                                    color: Color::rgba_linear(0.05, 0.05, 0.05, 0.6).into(),
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    parent
+                                        .spawn_bundle(NodeBundle {
+                                            style: Style {
+                                                size: Size::new(
+                                                    Val::Percent(0.0),
+                                                    Val::Percent(100.0),
+                                                ),
+                                                ..default()
+                                            },
+                                            color: XP_BAR.into(),
+                                            ..default()
+                                        })
+                                        .insert(PlayerXpBar);
+                                });
+                            parent
+                                .spawn_bundle(TextBundle {
+                                    text: Text {
+                                        sections: vec![
+                                            TextSection {
+                                                value: "Gold: ".to_string(),
+                                                style: styles.text(),
+                                            },
+                                            TextSection {
+                                                value: String::new(),
+                                                style: styles.text(),
+                                            },
+                                        ],
+                                        alignment: Default::default(),
+                                    },
+                                    ..default()
+                                })
+                                .insert(PlayerGoldText);
+                        });
+                });
+
+            // Game log
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(480.0), Val::Px(VISIBLE_LOG_LINES as f32 * 22.0)),
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            left: Val::Px(0.0),
+                            bottom: Val::Px(40.0),
+                            ..default()
+                        },
+                        padding: Rect::all(Val::Px(4.0)),
+                        align_items: AlignItems::FlexStart,
+                        ..default()
+                    },
+                    color: Color::rgba_linear(0.05, 0.05, 0.05, 0.3).into(),
+                    ..default()
+                })
+                .insert(GameLogPanel)
+                .insert(Interaction::default())
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(TextBundle::default())
+                        .insert(GameLogText::default());
                 });
 
             // Inventory bar
@@ -166,29 +311,168 @@ fn add_newly_carried_item_to_inventory(
         (Entity, &Name, &Handle<Image>, &CarriedBy),
         (With<Item>, Added<CarriedBy>),
     >,
+    inventory_query: Query<&RepresentsItem>,
 ) {
+    // Items may already have been added by `set_up_hud`, e.g. after loading a game
+    let new_items = new_items_query.iter().filter(|(item, ..)| {
+        !inventory_query
+            .iter()
+            .any(|&RepresentsItem(represented)| represented == *item)
+    });
     commands
         .entity(inventory_bar_query.single())
         .with_children(|parent| {
-            populate_inventory(parent, player_query.iter().next(), new_items_query.iter());
+            populate_inventory(parent, player_query.iter().next(), new_items);
         });
 }
 
 fn inventory_item_interaction(
     mut commands: Commands,
-    interaction_query: Query<
-        (Entity, &Interaction, &RepresentsItem),
-        (Changed<Interaction>, With<Button>),
-    >,
+    interaction_query: Query<(&Interaction, &RepresentsItem), (Changed<Interaction>, With<Button>)>,
 ) {
-    for (inventory_item, &interaction, &RepresentsItem(item)) in interaction_query.iter() {
+    for (&interaction, &RepresentsItem(item)) in interaction_query.iter() {
         if interaction == Interaction::Clicked {
             commands.entity(item).insert(Used);
+        }
+    }
+}
+
+fn inventory_slot_hotkeys(
+    mut commands: Commands,
+    mut keyboard_input_events: EventReader<KeyboardInput>,
+    input_bindings: Res<InputBindings>,
+    inventory_bar_query: Query<&Children, With<InventoryBar>>,
+    inventory_item_query: Query<&RepresentsItem>,
+) {
+    for event in keyboard_input_events.iter() {
+        if let KeyboardInput {
+            key_code: Some(key),
+            state: ElementState::Pressed,
+            ..
+        } = event
+        {
+            if let Some(InputAction::UseInventorySlot(slot)) = input_bindings.action(*key) {
+                // Slots are numbered starting at one, as they are on the keyboard
+                let inventory_item = slot.checked_sub(1).and_then(|idx| {
+                    inventory_bar_query
+                        .iter()
+                        .flat_map(|children| children.iter())
+                        .filter(|&&child| inventory_item_query.get(child).is_ok())
+                        .nth(idx)
+                });
+                if let Some(&inventory_item) = inventory_item {
+                    let RepresentsItem(item) = inventory_item_query.get(inventory_item).unwrap();
+                    commands.entity(*item).insert(Used);
+                }
+            }
+        }
+    }
+}
+
+// Items stay in the inventory until they are used up, as worn equipment and keys are kept
+fn remove_spent_items_from_inventory(
+    mut commands: Commands,
+    inventory_query: Query<(Entity, &RepresentsItem)>,
+    item_query: Query<(), With<Item>>,
+) {
+    for (inventory_item, &RepresentsItem(item)) in inventory_query.iter() {
+        if item_query.get(item).is_err() {
             commands.entity(inventory_item).despawn_recursive();
         }
     }
 }
 
+fn highlight_equipped_items(
+    mut inventory_query: Query<(&RepresentsItem, &mut UiColor)>,
+    equipped_query: Query<(), With<Equipped>>,
+) {
+    for (&RepresentsItem(item), mut color) in inventory_query.iter_mut() {
+        let highlight = if equipped_query.get(item).is_ok() {
+            EQUIPPED_ITEM
+        } else {
+            Color::NONE
+        };
+        if color.0 != highlight {
+            color.0 = highlight;
+        }
+    }
+}
+
+fn update_gold_hud(
+    player_query: Query<(&Gold, ChangeTrackers<Gold>), With<Player>>,
+    mut text_query: Query<(&mut Text, ChangeTrackers<PlayerGoldText>), With<PlayerGoldText>>,
+) {
+    for (&Gold(gold), gold_tracker) in player_query.iter() {
+        for (mut text, text_tracker) in text_query.iter_mut() {
+            if gold_tracker.is_changed() || text_tracker.is_changed() {
+                text.sections[1].value = format!("{}", gold);
+            }
+        }
+    }
+}
+
+fn update_experience_hud(
+    player_query: Query<(&Experience, ChangeTrackers<Experience>), With<Player>>,
+    mut text_query: Query<(&mut Text, ChangeTrackers<PlayerLevelText>), With<PlayerLevelText>>,
+    mut bar_query: Query<&mut Style, With<PlayerXpBar>>,
+) {
+    for (experience, experience_tracker) in player_query.iter() {
+        for (mut text, text_tracker) in text_query.iter_mut() {
+            if experience_tracker.is_changed() || text_tracker.is_changed() {
+                text.sections[0].value = format!("Level {}", experience.level);
+                for mut style in bar_query.iter_mut() {
+                    let progress = experience.xp as f32 / experience.xp_to_next_level() as f32;
+                    style.size.width = Val::Percent(100.0 * progress.min(1.0));
+                }
+            }
+        }
+    }
+}
+
+// Shows an icon for each effect the player is under, with the number of turns it lasts
+fn update_status_effects_hud(
+    mut commands: Commands,
+    styles: Res<UiStyles>,
+    asset_server: Res<AssetServer>,
+    player_query: Query<(&StatusEffects, ChangeTrackers<StatusEffects>), With<Player>>,
+    bar_query: Query<(Entity, ChangeTrackers<StatusEffectsBar>), With<StatusEffectsBar>>,
+) {
+    for (StatusEffects(status_effects), status_tracker) in player_query.iter() {
+        for (bar, bar_tracker) in bar_query.iter() {
+            if !status_tracker.is_changed() && !bar_tracker.is_changed() {
+                continue;
+            }
+
+            commands.entity(bar).despawn_descendants();
+            commands.entity(bar).with_children(|parent| {
+                for effect in status_effects {
+                    parent.spawn_bundle(ImageBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(24.0), Val::Px(24.0)),
+                            margin: Rect {
+                                left: Val::Px(8.0),
+                                right: Val::Px(2.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        image: UiImage(asset_server.load(effect.kind.icon())),
+                        ..default()
+                    });
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            effect.turns_left.to_string(),
+                            styles.text(),
+                            Default::default(),
+                        ),
+                        ..default()
+                    });
+                }
+            });
+        }
+    }
+}
+
 fn tear_down_hud(mut commands: Commands, hud_query: Query<Entity, With<HudItem>>) {
     for hud_item in hud_query.iter() {
         commands.entity(hud_item).despawn_recursive();
@@ -216,3 +500,57 @@ fn update_health_hud(
         }
     }
 }
+
+fn log_color(category: LogCategory) -> Color {
+    match category {
+        LogCategory::Info => Color::WHITE,
+        LogCategory::Combat => Color::rgb(1.0, 0.45, 0.45),
+        LogCategory::Item => Color::rgb(1.0, 0.85, 0.35),
+    }
+}
+
+fn scroll_game_log(
+    mut wheel_events: EventReader<MouseWheel>,
+    panel_query: Query<&Interaction, With<GameLogPanel>>,
+    mut text_query: Query<&mut GameLogText>,
+    game_log: Res<GameLog>,
+) {
+    let scroll_delta: f32 = wheel_events.iter().map(|event| event.y).sum();
+    if scroll_delta == 0.0 || !panel_query.iter().any(|&i| i == Interaction::Hovered) {
+        return;
+    }
+
+    let max_scroll = game_log.messages().len().saturating_sub(VISIBLE_LOG_LINES);
+    for mut log_text in text_query.iter_mut() {
+        let scroll = log_text.scroll as f32 + scroll_delta.signum();
+        log_text.scroll = (scroll.max(0.0) as usize).min(max_scroll);
+    }
+}
+
+fn update_game_log_hud(
+    styles: Res<UiStyles>,
+    game_log: Res<GameLog>,
+    mut text_query: Query<(&mut Text, &GameLogText, ChangeTrackers<GameLogText>)>,
+) {
+    for (mut text, log_text, log_text_tracker) in text_query.iter_mut() {
+        if game_log.is_changed() || log_text_tracker.is_changed() {
+            let visible_messages = game_log
+                .messages()
+                .rev()
+                .skip(log_text.scroll)
+                .take(VISIBLE_LOG_LINES)
+                .collect::<Vec<_>>();
+            text.sections = visible_messages
+                .into_iter()
+                .rev()
+                .map(|message| TextSection {
+                    value: format!("{}\n", message.text),
+                    style: TextStyle {
+                        color: log_color(message.category),
+                        ..styles.text()
+                    },
+                })
+                .collect();
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use super::{UiState, UiStyles};
 use crate::prelude::*;
+use bevy::math::Rect;
 
 use bevy_ui_navigation::{
     components::FocusableButtonBundle, event_helpers::NavEventQuery, FocusState, Focusable,
@@ -24,6 +25,7 @@ impl Plugin for MenuPlugin {
                     .after(NavRequestSystem)
                     .with_system(button_system)
                     .with_system(handle_nav_events)
+                    .with_system(update_diagonal_movement_label)
                     .into(),
             );
     }
@@ -32,16 +34,24 @@ impl Plugin for MenuPlugin {
 #[derive(Component, Debug)]
 struct MenuItem;
 
+#[derive(Component, Debug)]
+struct DiagonalMovementLabel;
+
 #[derive(Component, Clone, Copy, Debug, Eq, PartialEq)]
 enum ButtonAction {
     ResumeGame,
+    SaveGame,
+    LoadGame,
     RestartGame,
+    ToggleDiagonalMovement,
 }
 
 fn set_up_menu(
     mut commands: Commands,
     styles: Res<UiStyles>,
     turn_state: Res<CurrentState<TurnState>>,
+    rng: Res<GameRng>,
+    new_game_rules: Res<NewGameMovementRules>,
 ) {
     use TurnState::*;
     let CurrentState(current_turn_state) = *turn_state;
@@ -88,62 +98,88 @@ fn set_up_menu(
                 ..default()
             });
 
-            if current_turn_state == Pause {
-                parent
-                    .spawn_bundle(FocusableButtonBundle {
-                        button_bundle: ButtonBundle {
-                            style: Style {
-                                size: Size::new(Val::Px(200.0), Val::Px(50.0)),
-                                justify_content: JustifyContent::Center,
-                                align_items: AlignItems::Center,
-                                margin: Rect::all(Val::Px(10.0)),
-                                ..default()
-                            },
-                            color: NORMAL_BUTTON.into(),
-                            ..default()
-                        },
+            if current_turn_state != Loading {
+                parent.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        format!("Seed: {}", rng.seed()),
+                        styles.text(),
+                        Default::default(),
+                    ),
+                    style: Style {
+                        margin: Rect::all(Val::Px(10.0)),
                         ..default()
-                    })
-                    .insert(ButtonAction::ResumeGame)
-                    .with_children(|parent| {
-                        parent.spawn_bundle(TextBundle {
-                            text: Text::with_section("Resume", styles.text(), Default::default()),
-                            ..default()
-                        });
-                    });
+                    },
+                    ..default()
+                });
+            }
+
+            if current_turn_state == Pause {
+                spawn_button(parent, &styles, ButtonAction::ResumeGame, "Resume");
+                if SAVES_ENABLED {
+                    spawn_button(parent, &styles, ButtonAction::SaveGame, "Save game");
+                    spawn_button(parent, &styles, ButtonAction::LoadGame, "Load game");
+                }
             }
 
             if current_turn_state != Loading {
-                parent
-                    .spawn_bundle(FocusableButtonBundle {
-                        button_bundle: ButtonBundle {
-                            style: Style {
-                                size: Size::new(Val::Px(200.0), Val::Px(50.0)),
-                                justify_content: JustifyContent::Center,
-                                align_items: AlignItems::Center,
-                                margin: Rect::all(Val::Px(10.0)),
-                                ..default()
-                            },
-                            color: NORMAL_BUTTON.into(),
-                            ..default()
-                        },
-                        ..default()
-                    })
-                    .insert(ButtonAction::RestartGame)
-                    .with_children(|parent| {
-                        parent.spawn_bundle(TextBundle {
-                            text: Text::with_section(
-                                restart_text,
-                                styles.text(),
-                                Default::default(),
-                            ),
-                            ..default()
-                        });
-                    });
+                spawn_button(
+                    parent,
+                    &styles,
+                    ButtonAction::ToggleDiagonalMovement,
+                    diagonal_movement_label(new_game_rules.0.diagonal),
+                );
+                spawn_button(parent, &styles, ButtonAction::RestartGame, restart_text);
+            }
+        });
+}
+
+fn spawn_button(parent: &mut ChildBuilder, styles: &UiStyles, action: ButtonAction, label: &str) {
+    parent
+        .spawn_bundle(FocusableButtonBundle {
+            button_bundle: ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    margin: Rect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(action)
+        .with_children(|parent| {
+            let mut label = parent.spawn_bundle(TextBundle {
+                text: Text::with_section(label, styles.text(), Default::default()),
+                ..default()
+            });
+            if action == ButtonAction::ToggleDiagonalMovement {
+                label.insert(DiagonalMovementLabel);
             }
         });
 }
 
+fn diagonal_movement_label(diagonal: bool) -> &'static str {
+    if diagonal {
+        "Diagonal moves: On"
+    } else {
+        "Diagonal moves: Off"
+    }
+}
+
+fn update_diagonal_movement_label(
+    new_game_rules: Res<NewGameMovementRules>,
+    mut label_query: Query<&mut Text, With<DiagonalMovementLabel>>,
+) {
+    if new_game_rules.is_changed() {
+        for mut text in label_query.iter_mut() {
+            text.sections[0].value = diagonal_movement_label(new_game_rules.0.diagonal).into();
+        }
+    }
+}
+
 fn tear_down_menu(mut commands: Commands, menu_query: Query<Entity, With<MenuItem>>) {
     for menu_item in menu_query.iter() {
         commands.entity(menu_item).despawn_recursive();
@@ -161,7 +197,13 @@ fn button_system(mut focusables: Query<(&Focusable, &mut UiColor), Changed<Focus
     }
 }
 
-fn handle_nav_events(mut commands: Commands, mut buttons: NavEventQuery<&mut ButtonAction>) {
+fn handle_nav_events(
+    mut commands: Commands,
+    mut buttons: NavEventQuery<&mut ButtonAction>,
+    mut save_msgs: EventWriter<WantsToSave>,
+    mut load_msgs: EventWriter<WantsToLoad>,
+    mut new_game_rules: ResMut<NewGameMovementRules>,
+) {
     match buttons
         .single_activated_mut()
         .deref_mut()
@@ -170,9 +212,19 @@ fn handle_nav_events(mut commands: Commands, mut buttons: NavEventQuery<&mut But
         Some(ButtonAction::ResumeGame) => {
             commands.insert_resource(NextState(TurnState::AwaitingInput));
         }
+        Some(ButtonAction::SaveGame) => {
+            save_msgs.send(WantsToSave);
+        }
+        Some(ButtonAction::LoadGame) => {
+            load_msgs.send(WantsToLoad);
+        }
         Some(ButtonAction::RestartGame) => {
             commands.insert_resource(NextState(TurnState::NewGame));
         }
+        // The running game keeps its rules, the option only applies to the next one
+        Some(ButtonAction::ToggleDiagonalMovement) => {
+            new_game_rules.0.diagonal = !new_game_rules.0.diagonal;
+        }
         None => {}
     }
 }
@@ -40,7 +40,7 @@ fn sync_ui_state(
 
     use TurnState::*;
     let supposed_ui_state = match current_turn_state {
-        NewGame | AwaitingInput | PlayerTurn | MonsterTurn => UiState::Hud,
+        NewGame | NextLevel | AwaitingInput | PlayerTurn | MonsterTurn => UiState::Hud,
         Loading | Victory | Defeat | Pause => UiState::Menu,
     };
 